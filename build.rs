@@ -0,0 +1,40 @@
+// build.rs
+// Embeds the default templates/ tree in the binary
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Every file under a directory, sorted so the generated table is stable
+fn template_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            template_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let mut files = Vec::new();
+    template_files(&root, &mut files)?;
+    files.sort();
+
+    // A slice of (path relative to templates/, contents) pairs
+    let mut table = String::from("&[\n");
+    for file in &files {
+        let relative = file.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        table.push_str(&format!("    ({:?}, include_bytes!({:?})),\n", relative, file.display().to_string()));
+    }
+    table.push_str("]\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("built_in_templates.rs"), table)
+}
@@ -65,11 +65,7 @@ if [[ ! -f "target/release/flutter_lazy" ]]; then
     error_exit "Build completed but binary not found."
 fi
 
-# Copy templates to target directory
-display_step "Setting up templates..."
-echo "Copying templates..."
-mkdir -p target/release/templates || error_exit "Failed to create templates directory"
-cp -r templates/* target/release/templates/ || error_exit "Failed to copy template files"
+# Templates are embedded in the binary, so only the binary needs installing
 
 echo -e "${GREEN}Build complete!${NC}"
 
@@ -0,0 +1,383 @@
+// config.rs
+// Configuration file support for non-interactive project generation
+
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Context, Result};
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+use crate::swagger::GroupingStrategy;
+
+/// Flavors offered when none are configured
+pub const DEFAULT_FLAVORS: [&str; 3] = ["dev", "stage", "prod"];
+
+/// Features that `new` knows how to generate
+pub const AVAILABLE_FEATURES: [&str; 3] = ["auth", "notifications", "main_page"];
+
+/// Settings read from a `flutter_lazy.yaml` config file
+///
+/// Every field is optional; values given on the command line take precedence
+/// and anything still missing is prompted for (or defaulted) later.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Written into project manifests; accepted so a manifest can be reused as a config
+    #[serde(default, skip_serializing)]
+    pub template_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flavors: Option<Vec<FlavorConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<ApiConfig>,
+}
+
+/// Settings of one flavor
+///
+/// A plain name (`- qa`) takes the defaults for everything else, and flavors
+/// without overrides are written back as plain names.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "FlavorEntry", into = "FlavorEntry")]
+pub struct FlavorConfig {
+    pub name: String,
+    /// App name shown on the device
+    pub display_name: Option<String>,
+    /// API base URL the flavor talks to
+    pub base_url: Option<String>,
+    /// Appended to the application and bundle id, e.g. `.qa`
+    pub suffix: Option<String>,
+}
+
+/// How a flavor is written in config files and manifests
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum FlavorEntry {
+    Name(String),
+    Settings {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        display_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base_url: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suffix: Option<String>,
+    },
+}
+
+impl From<FlavorEntry> for FlavorConfig {
+    fn from(entry: FlavorEntry) -> Self {
+        match entry {
+            FlavorEntry::Name(name) => FlavorConfig::named(&name),
+            FlavorEntry::Settings { name, display_name, base_url, suffix } => FlavorConfig {
+                name,
+                display_name,
+                base_url,
+                suffix,
+            },
+        }
+    }
+}
+
+impl From<FlavorConfig> for FlavorEntry {
+    fn from(flavor: FlavorConfig) -> Self {
+        match flavor {
+            FlavorConfig { name, display_name: None, base_url: None, suffix: None } => FlavorEntry::Name(name),
+            FlavorConfig { name, display_name, base_url, suffix } => FlavorEntry::Settings {
+                name,
+                display_name,
+                base_url,
+                suffix,
+            },
+        }
+    }
+}
+
+impl FlavorConfig {
+    /// A flavor with default settings
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: None,
+            base_url: None,
+            suffix: None,
+        }
+    }
+
+    /// Production builds keep the plain app name, API host and bundle id
+    fn is_production(&self) -> bool {
+        matches!(self.name.as_str(), "prod" | "production")
+    }
+
+    /// App name, by default the project title followed by the flavor name
+    pub fn display_name(&self, project_title: &str) -> String {
+        match &self.display_name {
+            Some(display_name) => display_name.clone(),
+            None if self.is_production() => project_title.to_string(),
+            None => format!("{} {}", project_title, self.name.to_case(Case::Title)),
+        }
+    }
+
+    /// API base URL, by default `https://<flavor>-api.example.com`
+    pub fn base_url(&self) -> String {
+        match &self.base_url {
+            Some(base_url) => base_url.clone(),
+            None if self.is_production() => "https://api.example.com".to_string(),
+            None => format!("https://{}-api.example.com", self.name.to_case(Case::Kebab)),
+        }
+    }
+
+    /// Application/bundle id suffix, by default `.<flavor>` without underscores,
+    /// which iOS bundle ids do not allow
+    pub fn suffix(&self) -> String {
+        match &self.suffix {
+            Some(suffix) => suffix.clone(),
+            None if self.is_production() => String::new(),
+            None => format!(".{}", self.name.replace('_', "")),
+        }
+    }
+}
+
+/// Flavors for names given on the command line, keeping settings the config file has for them
+pub fn flavors_from_names(names: &[String], configured: Option<&[FlavorConfig]>) -> Vec<FlavorConfig> {
+    names
+        .iter()
+        .map(|name| {
+            configured
+                .and_then(|flavors| flavors.iter().find(|flavor| &flavor.name == name))
+                .cloned()
+                .unwrap_or_else(|| FlavorConfig::named(name))
+        })
+        .collect()
+}
+
+/// API specification settings inside the config file
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfig {
+    /// Set to `false` to skip API generation without being asked
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domains: Option<Vec<String>>,
+    /// Dart types overriding the defaults, keyed by `type` or `type:format`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub type_mappings: BTreeMap<String, String>,
+    /// How operations are grouped into features: tag, path-prefix, operation-id or single
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub grouping: GroupingStrategy,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            url: None,
+            file: None,
+            domains: None,
+            type_mappings: BTreeMap::new(),
+            grouping: GroupingStrategy::default(),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Load a config file; relative paths inside it are resolved against the file's directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+        let mut config: ConfigFile = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        if let Some(output) = &config.output {
+            config.output = Some(resolve_relative(base_dir, output));
+        }
+        if let Some(api) = &mut config.api {
+            if let Some(file) = &api.file {
+                api.file = Some(resolve_relative(base_dir, file));
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// A path from a file in `base_dir`, as given if absolute or joined onto `base_dir`
+pub fn resolve_relative(base_dir: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    }
+}
+
+/// Whether prompts may be shown: stdin must be a terminal and `--yes` not given
+pub fn is_interactive(assume_yes: bool) -> bool {
+    !assume_yes && std::io::stdin().is_terminal()
+}
+
+/// Check that every flavor name can be used as a Dart enum value and file suffix,
+/// that suffixes are valid in both Android application ids and iOS bundle ids,
+/// and that display names fit on a line of an iOS build setting
+pub fn validate_flavors(flavors: &[FlavorConfig]) -> Result<()> {
+    if flavors.is_empty() {
+        return Err(anyhow::anyhow!("At least one flavor is required"));
+    }
+
+    for (index, flavor) in flavors.iter().enumerate() {
+        let mut chars = flavor.name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+            && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            return Err(anyhow::anyhow!(
+                "Invalid flavor name '{}': use lowercase letters, digits and underscores, starting with a letter",
+                flavor.name
+            ));
+        }
+        if matches!(flavor.name.as_str(), "main" | "test") {
+            return Err(anyhow::anyhow!("Invalid flavor name '{}': it is reserved by Android", flavor.name));
+        }
+        if flavors[..index].iter().any(|other| other.name == flavor.name) {
+            return Err(anyhow::anyhow!("Flavor '{}' is listed twice", flavor.name));
+        }
+
+        let suffix = flavor.suffix();
+        let valid_suffix = suffix.is_empty()
+            || suffix.strip_prefix('.').is_some_and(|rest| rest.split('.').all(|segment| {
+                segment.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && segment.chars().all(|c| c.is_ascii_alphanumeric())
+            }));
+        if !valid_suffix {
+            return Err(anyhow::anyhow!(
+                "Invalid suffix '{}' for flavor '{}': use dot-separated segments of letters and digits, like .qa",
+                suffix, flavor.name
+            ));
+        }
+
+        // Other characters are escaped for each file the name is written to
+        let display_name = flavor.display_name.as_deref().unwrap_or_default();
+        if display_name.contains(|c: char| c == '$' || c.is_control()) {
+            return Err(anyhow::anyhow!(
+                "Invalid display name '{}' for flavor '{}': `$` and line breaks cannot be used in iOS build settings",
+                display_name.escape_debug(), flavor.name
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that every requested feature is one `new` can generate
+pub fn validate_features(features: &[String]) -> Result<()> {
+    for feature in features {
+        if !AVAILABLE_FEATURES.contains(&feature.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Unknown feature '{}'. Available features: {}",
+                feature,
+                AVAILABLE_FEATURES.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_file() {
+        let yaml = r#"
+name: shop
+package_name: com.acme.shop
+flavors: [dev, qa]
+features: [auth]
+api:
+  file: specs/openapi.json
+  domains: [pets]
+  grouping: path-prefix
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.name.as_deref(), Some("shop"));
+        assert_eq!(config.flavors.unwrap(), vec![FlavorConfig::named("dev"), FlavorConfig::named("qa")]);
+
+        let api = config.api.unwrap();
+        assert!(api.enabled);
+        assert_eq!(api.domains.unwrap(), vec!["pets"]);
+        assert_eq!(api.grouping, GroupingStrategy::PathPrefix);
+    }
+
+    #[test]
+    fn test_flavor_settings() {
+        let yaml = r#"
+- dev
+- name: qa
+  display_name: Shop QA
+  base_url: https://qa.acme.com
+  suffix: .test.qa
+"#;
+        let flavors: Vec<FlavorConfig> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(flavors[0].display_name("Shop"), "Shop Dev");
+        assert_eq!(flavors[0].base_url(), "https://dev-api.example.com");
+        assert_eq!(flavors[0].suffix(), ".dev");
+        assert_eq!(flavors[1].display_name("Shop"), "Shop QA");
+        assert_eq!(flavors[1].suffix(), ".test.qa");
+        assert_eq!(FlavorConfig::named("prod").suffix(), "");
+
+        // Flavors without overrides stay plain names
+        let written = serde_yaml::to_string(&flavors).unwrap();
+        assert!(written.starts_with("- dev\n- name: qa\n"), "{}", written);
+
+        let from_cli = flavors_from_names(&["qa".into(), "uat".into()], Some(&flavors));
+        assert_eq!(from_cli[0], flavors[1]);
+        assert_eq!(from_cli[1], FlavorConfig::named("uat"));
+    }
+
+    #[test]
+    fn test_unknown_config_keys_are_rejected() {
+        let result: Result<ConfigFile, _> = serde_yaml::from_str("flavours: [dev]");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_flavors_and_features() {
+        let flavors = |names: &[&str]| names.iter().map(|name| FlavorConfig::named(name)).collect::<Vec<_>>();
+        assert!(validate_flavors(&flavors(&["dev", "uat_2"])).is_ok());
+        assert!(validate_flavors(&flavors(&["Dev"])).is_err());
+        assert!(validate_flavors(&flavors(&["dev", "dev"])).is_err());
+        assert!(validate_flavors(&flavors(&["test"])).is_err());
+        assert!(validate_flavors(&[]).is_err());
+        let bad_suffix = FlavorConfig { suffix: Some("qa".into()), ..FlavorConfig::named("qa") };
+        assert!(validate_flavors(&[bad_suffix]).is_err());
+        let display_name = |name: &str| FlavorConfig { display_name: Some(name.into()), ..FlavorConfig::named("qa") };
+        assert!(validate_flavors(&[display_name("Bob's \"Shop\" // QA")]).is_ok());
+        assert!(validate_flavors(&[display_name("$HOME")]).is_err());
+        assert!(validate_flavors(&[display_name("Shop\nQA")]).is_err());
+
+        assert!(validate_features(&["auth".into(), "main_page".into()]).is_ok());
+        assert!(validate_features(&["payments".into()]).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+// dependencies.rs
+// Packages the generated code needs, added to pubspec.yaml with versions from a catalog
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::pubspec::Pubspec;
+use crate::templates;
+use crate::vfs;
+
+/// Template pinning the version of every package, overridable package by package
+const CATALOG: &str = "versions.yaml";
+
+/// Packages one part of the generated code imports
+#[derive(Debug, Clone, Copy)]
+pub struct Packages {
+    pub dependencies: &'static [&'static str],
+    /// Code generators the part's annotations need
+    pub dev_dependencies: &'static [&'static str],
+}
+
+/// Package versions from the catalog
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Catalog {
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, String>,
+}
+
+impl Catalog {
+    /// The built-in catalog with the versions of every override applied over it
+    fn load() -> Result<Self> {
+        Self::from_layers(&templates::layers(CATALOG)?)
+    }
+
+    fn from_layers(layers: &[String]) -> Result<Self> {
+        let mut catalog = Catalog::default();
+        for layer in layers {
+            let versions: Catalog = serde_yaml::from_str(layer)
+                .with_context(|| format!("Failed to parse {}", CATALOG))?;
+            catalog.dependencies.extend(versions.dependencies);
+            catalog.dev_dependencies.extend(versions.dev_dependencies);
+        }
+        Ok(catalog)
+    }
+}
+
+/// Version of a package, failing for packages the catalog does not pin
+fn version<'a>(versions: &'a BTreeMap<String, String>, package: &str) -> Result<&'a str> {
+    versions
+        .get(package)
+        .map(String::as_str)
+        .with_context(|| format!("No version for package '{}' in {}", package, CATALOG))
+}
+
+/// Add every package the parts need to a pubspec, keeping the versions it
+/// already has; returns the packages added
+pub fn add_packages(pubspec: &mut Pubspec, parts: &[Packages]) -> Result<Vec<String>> {
+    let catalog = Catalog::load()?;
+    let dependencies: BTreeSet<&str> = parts.iter().flat_map(|part| part.dependencies.iter().copied()).collect();
+    let dev_dependencies: BTreeSet<&str> = parts.iter().flat_map(|part| part.dev_dependencies.iter().copied()).collect();
+
+    let mut added = Vec::new();
+    for package in dependencies {
+        if pubspec.add_dependency(package, version(&catalog.dependencies, package)?)? {
+            added.push(package.to_string());
+        }
+    }
+    for package in dev_dependencies {
+        if pubspec.add_dev_dependency(package, version(&catalog.dev_dependencies, package)?)? {
+            added.push(package.to_string());
+        }
+    }
+    Ok(added)
+}
+
+/// Add the packages the parts need to a project's pubspec.yaml, if it has one
+pub fn add_to_project(project_dir: &Path, parts: &[Packages]) -> Result<()> {
+    if !vfs::exists(project_dir.join("pubspec.yaml")) {
+        return Ok(());
+    }
+
+    let mut pubspec = Pubspec::load(project_dir)?;
+    let added = add_packages(&mut pubspec, parts)?;
+    if !added.is_empty() {
+        pubspec.save()?;
+        println!("✅ Added to pubspec.yaml: {}", added.join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::{self, FeatureParams};
+
+    /// Every part of the generator that declares packages
+    fn declared() -> Vec<Packages> {
+        let mut parts = FeatureParams::new("any").packages();
+        parts.extend([
+            crate::generator::PROJECT_PACKAGES,
+            crate::swagger::API_PACKAGES,
+            features::auth::PACKAGES,
+            features::main_page::PACKAGES,
+            features::notifications::PACKAGES,
+        ]);
+        parts
+    }
+
+    #[test]
+    fn test_templates_imports_are_declared() {
+        let catalog = Catalog::load().unwrap();
+        let parts = declared();
+        let dependencies: BTreeSet<&str> = parts.iter().flat_map(|part| part.dependencies.iter().copied()).collect();
+        for package in &dependencies {
+            assert!(catalog.dependencies.contains_key(*package), "{} has no version", package);
+        }
+        for package in parts.iter().flat_map(|part| part.dev_dependencies.iter()) {
+            assert!(catalog.dev_dependencies.contains_key(*package), "{} has no version", package);
+        }
+
+        for entry in walkdir::WalkDir::new("templates").into_iter().map(Result::unwrap) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(entry.path()) else { continue };
+            for import in content.split("import 'package:").skip(1) {
+                let package = import.split('/').next().unwrap();
+                // The project's own package is named by a variable
+                assert!(
+                    package == "flutter" || package.starts_with("{{") || dependencies.contains(package),
+                    "{} imports undeclared package {}",
+                    entry.path().display(),
+                    package
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_catalog_overrides_per_package() {
+        let layers = [
+            "dependencies:\n  dio: ^5.4.3\n  go_router: ^14.1.4\n".to_string(),
+            "dependencies:\n  dio: ^5.0.0\n".to_string(),
+        ];
+        let catalog = Catalog::from_layers(&layers).unwrap();
+        assert_eq!(catalog.dependencies["dio"], "^5.0.0");
+        assert_eq!(catalog.dependencies["go_router"], "^14.1.4");
+        assert!(Catalog::from_layers(&["packages: {}\n".to_string()]).is_err());
+    }
+}
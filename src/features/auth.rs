@@ -1,11 +1,19 @@
 // features/auth.rs
 // Auth feature generator
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use anyhow::{Context, Result};
-use std::fs;
+use crate::dependencies::{self, Packages};
+use crate::vfs;
 use crate::features::{FeatureParams, create_feature, update_main_router, update_main_di};
-use crate::utils::copy_template_file;
+
+/// Packages the specialized files of the authentication feature import
+pub const PACKAGES: Packages = Packages {
+    dependencies: &["bloc", "dartz", "easy_localization", "equatable", "flutter_bloc", "formz", "get_it", "go_router", "injectable", "json_annotation"],
+    dev_dependencies: &["build_runner", "injectable_generator", "json_serializable"],
+};
+use crate::templates::{ProjectContext, TemplateContext};
+use crate::utils::generate_template_file;
 
 /// Create an authentication feature with all required components
 pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
@@ -15,7 +23,7 @@ pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
     
     // Check if the feature already exists
     let feature_dir = project_dir.join("lib/features/auth");
-    if feature_dir.exists() {
+    if vfs::exists(&feature_dir) {
         return Err(anyhow::anyhow!("Feature 'auth' already exists at {:?}", feature_dir));
     }
     
@@ -23,6 +31,8 @@ pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
     
     // Create the base directory structure but without generic state management
     create_feature(project_dir, params)?;
+    dependencies::add_to_project(project_dir, &[PACKAGES])?;
+    let context = TemplateContext::for_project(&ProjectContext::load(project_dir)?);
     
     // Track created files for summary
     let mut created_files = Vec::new();
@@ -58,7 +68,7 @@ pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
     
     // Create directories for specialized cubits
     for cubit in auth_cubits.iter() {
-        fs::create_dir_all(feature_dir.join("cubits").join(cubit))
+        vfs::create_dir_all(feature_dir.join("cubits").join(cubit))
             .context(format!("Failed to create {} directory", cubit))?;
     }
     
@@ -86,10 +96,11 @@ pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
     ];
     
     for (template, dest_path) in auth_cubit_templates.iter() {
-        copy_template_file(
+        generate_template_file(
             template,
-            &feature_dir.join(dest_path), 
-            &[]
+            &feature_dir.join(dest_path),
+            &context,
+            false
         ).context(format!("Failed to copy template {}", template))?;
         
         let file_type = if dest_path.contains("state.dart") { "State" } else { "Cubit" };
@@ -97,7 +108,7 @@ pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
     }
     
     // Add services directory which is specific to auth feature
-    fs::create_dir_all(feature_dir.join("services"))
+    vfs::create_dir_all(feature_dir.join("services"))
         .context("Failed to create services directory")?;
     
     // Create auth UI pages
@@ -106,33 +117,35 @@ pub fn create_auth_feature(project_dir: &Path) -> Result<()> {
         // We would create or copy templates for each page
         // For now, just create empty files to ensure directory structure
         let page_path = feature_dir.join("ui/pages").join(page);
-        std::fs::write(&page_path, "// TODO: Implement auth page\n")
+        vfs::write(&page_path, "// TODO: Implement auth page\n")
             .context(format!("Failed to create page file: {}", page))?;
         created_files.push(format!("- UI Page: {}", page_path.display()));
     }
     
     // Create auth-specific models and repositories
-    fs::create_dir_all(feature_dir.join("data/models"))
+    vfs::create_dir_all(feature_dir.join("data/models"))
         .context("Failed to create auth models directory")?;
     
-    fs::create_dir_all(feature_dir.join("data/repository"))
+    vfs::create_dir_all(feature_dir.join("data/repository"))
         .context("Failed to create auth repository directory")?;
     
     // Copy auth models
     let model_path = feature_dir.join("data/models/user_model.dart");
-    copy_template_file(
+    generate_template_file(
         "features/auth/data/models/user_model.dart.tmpl",
         &model_path,
-        &[]
+        &context,
+        false
     ).context("Failed to copy user model template")?;
     created_files.push(format!("- Model: {}", model_path.display()));
     
     // Copy auth repository
     let repo_path = feature_dir.join("data/repository/auth_repository.dart");
-    copy_template_file(
+    generate_template_file(
         "features/auth/data/repository/auth_repository.dart.tmpl",
         &repo_path,
-        &[]
+        &context,
+        false
     ).context("Failed to copy auth repository template")?;
     created_files.push(format!("- Repository: {}", repo_path.display()));
     
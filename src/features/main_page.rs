@@ -1,11 +1,20 @@
 // features/main_page.rs
 // Main page feature generator
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use anyhow::{Context, Result};
-use std::fs;
+use crate::dependencies::{self, Packages};
+use crate::vfs;
 use crate::features::{FeatureParams, create_feature, update_main_router, update_main_di};
 
+/// Packages the specialized files of the main page feature import
+pub const PACKAGES: Packages = Packages {
+    dependencies: &["equatable", "flutter_bloc", "get_it", "go_router"],
+    dev_dependencies: &[],
+};
+use crate::templates::{self, ProjectContext, TemplateContext};
+use crate::utils::generate_template_file;
+
 /// Create a main page feature with navigation components
 pub fn create_main_page_feature(project_dir: &Path) -> Result<()> {
     // Main page feature with bottom navigation,
@@ -14,7 +23,7 @@ pub fn create_main_page_feature(project_dir: &Path) -> Result<()> {
     
     // Check if the feature already exists
     let feature_dir = project_dir.join("lib/features/main_page");
-    if feature_dir.exists() {
+    if vfs::exists(&feature_dir) {
         return Err(anyhow::anyhow!("Feature 'main_page' already exists at {:?}", feature_dir));
     }
     
@@ -28,41 +37,34 @@ pub fn create_main_page_feature(project_dir: &Path) -> Result<()> {
     
     // Standard feature creation
     create_feature(project_dir, params)?;
+    dependencies::add_to_project(project_dir, &[PACKAGES])?;
     
     // Create main_page feature directory
     let feature_dir = project_dir.join("lib/features/main_page");
     
     // Add blocs directory instead of cubits
-    fs::create_dir_all(feature_dir.join("blocs"))
+    vfs::create_dir_all(feature_dir.join("blocs"))
         .context("Failed to create blocs directory")?;
     
     // Add widgets directory with navigation-specific components
-    fs::create_dir_all(feature_dir.join("ui/widgets"))
+    vfs::create_dir_all(feature_dir.join("ui/widgets"))
         .context("Failed to create widgets directory")?;
     
     // Create main page files using templates
     println!("Creating main page files...");
     
-    // Copy template files for UI pages
+    // Render template files for UI pages
+    let context = TemplateContext::for_project(&ProjectContext::load(project_dir)?);
     let template_pages = [
         ("main_tabs.dart.tmpl", "main_tabs.dart"),
         ("home_page.dart.tmpl", "home_page.dart"),
         ("settings_page.dart.tmpl", "settings_page.dart"),
     ];
     for (template, target) in template_pages.iter() {
-        let template_path = PathBuf::from("flutter_lazy/templates/features/main_page/ui/pages").join(template);
         let target_path = feature_dir.join("ui/pages").join(target);
-        
-        if let Ok(content) = std::fs::read_to_string(&template_path) {
-            std::fs::write(&target_path, content)
-                .context(format!("Failed to create page file: {}", target))?;
-            created_files.push(format!("- UI Page: {}", target_path.display()));
-        } else {
-            // Fallback if template is not found
-            std::fs::write(&target_path, "// TODO: Implement main page\n")
-                .context(format!("Failed to create page file: {}", target))?;
-            created_files.push(format!("- UI Page: {}", target_path.display()));
-        }
+        generate_template_file(&format!("features/main_page/ui/pages/{}", template), &target_path, &context, false)
+            .context(format!("Failed to create page file: {}", target))?;
+        created_files.push(format!("- UI Page: {}", target_path.display()));
     }
     
     // Copy template files for blocs
@@ -71,49 +73,49 @@ pub fn create_main_page_feature(project_dir: &Path) -> Result<()> {
         ("bottom_navigation_state.dart.tmpl", "bottom_navigation_state.dart"),
     ];
     for (template, target) in template_blocs.iter() {
-        let template_path = PathBuf::from("flutter_lazy/templates/features/main_page/blocs/bottom_navigation_cubit").join(template);
+        let template_path = format!("features/main_page/blocs/bottom_navigation_cubit/{}", template);
         let target_path = feature_dir.join("blocs/bottom_navigation_cubit").join(target);
         
         // Create directory if it doesn't exist
-        std::fs::create_dir_all(target_path.parent().unwrap())
+        vfs::create_dir_all(target_path.parent().unwrap())
             .context("Failed to create blocs directory")?;
             
-        if let Ok(content) = std::fs::read_to_string(&template_path) {
-            std::fs::write(&target_path, content)
+        if templates::find(&template_path)?.is_some() {
+            generate_template_file(&template_path, &target_path, &context, false)
                 .context(format!("Failed to create bloc file: {}", target))?;
             created_files.push(format!("- State Management: {}", target_path.display()));
         } else {
             // Fallback if template is not found
-            std::fs::write(&target_path, "// TODO: Implement navigation state management\n")
+            vfs::write(&target_path, "// TODO: Implement navigation state management\n")
                 .context(format!("Failed to create bloc file: {}", target))?;
             created_files.push(format!("- State Management: {}", target_path.display()));
         }
     }
     
     // Copy router file
-    let router_template = PathBuf::from("flutter_lazy/templates/features/main_page/router.dart.tmpl");
+    let router_template = "features/main_page/router.dart.tmpl";
     let router_path = feature_dir.join("router.dart");
-    if let Ok(content) = std::fs::read_to_string(&router_template) {
-        std::fs::write(&router_path, content)
+    if templates::find(router_template)?.is_some() {
+        generate_template_file(router_template, &router_path, &context, false)
             .context("Failed to create router file")?;
         created_files.push(format!("- Router: {}", router_path.display()));
     } else {
         // Fallback if template is not found
-        std::fs::write(&router_path, "// TODO: Implement main page router\n")
+        vfs::write(&router_path, "// TODO: Implement main page router\n")
             .context("Failed to create router file")?;
         created_files.push(format!("- Router: {}", router_path.display()));
     }
     
     // Copy DI file
-    let di_template = PathBuf::from("flutter_lazy/templates/features/main_page/di.dart.tmpl");
+    let di_template = "features/main_page/di.dart.tmpl";
     let di_path = feature_dir.join("di.dart");
-    if let Ok(content) = std::fs::read_to_string(&di_template) {
-        std::fs::write(&di_path, content)
+    if templates::find(di_template)?.is_some() {
+        generate_template_file(di_template, &di_path, &context, false)
             .context("Failed to create DI file")?;
         created_files.push(format!("- DI: {}", di_path.display()));
     } else {
         // Fallback if template is not found
-        std::fs::write(&di_path, "// TODO: Implement main page DI\n")
+        vfs::write(&di_path, "// TODO: Implement main page DI\n")
             .context("Failed to create DI file")?;
         created_files.push(format!("- DI: {}", di_path.display()));
     }
@@ -5,17 +5,55 @@ pub mod auth;
 pub mod main_page;
 pub mod notifications;
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use anyhow::{Context, Result};
 use convert_case::{Case, Casing};
-use std::fs;
-use crate::utils::copy_template_file;
+use crate::dependencies::{self, Packages};
+use crate::vfs;
+use crate::templates::{ProjectContext, TemplateContext};
+use crate::utils::generate_template_file;
 
 // Re-export feature functions
 pub use auth::create_auth_feature;
 pub use main_page::create_main_page_feature;
 pub use notifications::create_notification_feature;
 
+/// Packages the cubit and state of a feature import
+const STATE_MANAGEMENT: Packages = Packages {
+    dependencies: &["equatable", "flutter_bloc", "formz"],
+    dev_dependencies: &[],
+};
+
+/// Packages the repository of a feature imports
+const REPOSITORY: Packages = Packages {
+    dependencies: &["dartz", "dio"],
+    dev_dependencies: &[],
+};
+
+/// Packages the models of a feature import, with the generator of their JSON code
+const MODELS: Packages = Packages {
+    dependencies: &["equatable", "json_annotation"],
+    dev_dependencies: &["build_runner", "json_serializable"],
+};
+
+/// Packages the page and widgets of a feature import
+const PAGES: Packages = Packages {
+    dependencies: &["easy_localization", "flutter_bloc", "formz", "go_router"],
+    dev_dependencies: &[],
+};
+
+/// Packages the routes of a feature import
+const ROUTING: Packages = Packages {
+    dependencies: &["go_router"],
+    dev_dependencies: &[],
+};
+
+/// Packages the dependency injection of a feature imports
+const DI: Packages = Packages {
+    dependencies: &["dio", "get_it", "shared_preferences"],
+    dev_dependencies: &[],
+};
+
 /// Parameters for feature generation
 pub struct FeatureParams {
     pub name: String,
@@ -58,6 +96,7 @@ impl FeatureParams {
         }
     }
     
+    #[allow(dead_code)]
     pub fn ui_only(name: &str) -> Self {
         Self {
             name: name.to_string(),
@@ -72,6 +111,7 @@ impl FeatureParams {
         }
     }
     
+    #[allow(dead_code)]
     pub fn with_state_type(name: &str, state_type: &str) -> Self {
         let mut params = Self::new(name);
         
@@ -82,6 +122,21 @@ impl FeatureParams {
         
         params
     }
+
+    /// Packages the files generated with these parameters import
+    pub fn packages(&self) -> Vec<Packages> {
+        [
+            (self.has_state_management, STATE_MANAGEMENT),
+            (self.has_repository, REPOSITORY),
+            (self.has_repository && self.has_models, MODELS),
+            (self.has_pages, PAGES),
+            (self.needs_routing, ROUTING),
+            (self.needs_di, DI),
+        ]
+        .into_iter()
+        .filter_map(|(included, packages)| included.then_some(packages))
+        .collect()
+    }
 }
 
 /// Create a general feature with the given parameters
@@ -90,66 +145,68 @@ pub fn create_feature(project_dir: &Path, params: FeatureParams) -> Result<()> {
     let feature_dir = project_dir.join("lib/features").join(&feature_name);
     
     // Check if the feature already exists
-    if feature_dir.exists() {
+    if vfs::exists(&feature_dir) {
         return Err(anyhow::anyhow!("Feature '{}' already exists at {:?}", feature_name, feature_dir));
     }
     
     println!("Creating feature: {}", feature_name);
     
     // Create base directory
-    fs::create_dir_all(&feature_dir)
+    vfs::create_dir_all(&feature_dir)
         .with_context(|| format!("Failed to create feature directory: {:?}", feature_dir))?;
     
     // Create subdirectories based on feature parameters
     if params.has_state_management {
-        fs::create_dir_all(feature_dir.join("cubits"))
+        vfs::create_dir_all(feature_dir.join("cubits"))
             .context("Failed to create cubits directory")?;
     }
     
     if params.has_repository {
-        fs::create_dir_all(feature_dir.join("data/repository"))
+        vfs::create_dir_all(feature_dir.join("data/repository"))
             .context("Failed to create repository directory")?;
         
         if params.has_models {
-            fs::create_dir_all(feature_dir.join("data/models"))
+            vfs::create_dir_all(feature_dir.join("data/models"))
                 .context("Failed to create models directory")?;
         }
         
-        fs::create_dir_all(feature_dir.join("data/datasources"))
+        vfs::create_dir_all(feature_dir.join("data/datasources"))
             .context("Failed to create datasources directory")?;
     }
     
     if params.has_pages {
-        fs::create_dir_all(feature_dir.join("ui/pages"))
+        vfs::create_dir_all(feature_dir.join("ui/pages"))
             .context("Failed to create pages directory")?;
-        fs::create_dir_all(feature_dir.join("ui/_widgets"))
+        vfs::create_dir_all(feature_dir.join("ui/_widgets"))
             .context("Failed to create _widgets directory")?;
     }
     
     // Create services directory if needed
     if params.has_services {
-        fs::create_dir_all(feature_dir.join("services"))
+        vfs::create_dir_all(feature_dir.join("services"))
             .context("Failed to create services directory")?;
     }
     
     // Create utils directory if needed
     if params.has_utils {
-        fs::create_dir_all(feature_dir.join("utils"))
+        vfs::create_dir_all(feature_dir.join("utils"))
             .context("Failed to create utils directory")?;
     }
     
-    // Create template files with replacements
+    // Create template files from the project and feature context
+    let context = TemplateContext::for_feature(&ProjectContext::load(project_dir)?, &params);
     let pascal_name = params.name.to_case(Case::Pascal);
     let snake_name = params.name.to_case(Case::Snake);
-    let camel_name = params.name.to_case(Case::Camel);
     
+    dependencies::add_to_project(project_dir, &params.packages())?;
+
     // Generate basic template files
     generate_feature_files(
         &feature_dir, 
         &pascal_name,
         &snake_name,
-        &camel_name,
-        &params
+        &params,
+        &context
     )?;
     
     Ok(())
@@ -160,40 +217,35 @@ fn generate_feature_files(
     feature_dir: &Path, 
     pascal_name: &str,
     snake_name: &str,
-    camel_name: &str,
-    params: &FeatureParams
+    params: &FeatureParams,
+    context: &TemplateContext
 ) -> Result<()> {
-    // Common replacements for all templates
-    let replacements = [
-        ("FEATURE_NAME_PASCAL", pascal_name),
-        ("FEATURE_NAME_SNAKE", snake_name),
-        ("FEATURE_NAME_CAMEL", camel_name),
-    ];
-    
     // Track created files for summary
     let mut created_files = Vec::new();
     
     // Generate state management files if needed
     if params.has_state_management {
         // Create cubit directory
-        fs::create_dir_all(feature_dir.join("cubits").join(format!("{}_cubit", snake_name)))
+        vfs::create_dir_all(feature_dir.join("cubits").join(format!("{}_cubit", snake_name)))
             .context("Failed to create cubit directory")?;
         
         // Create cubit files with simplified boilerplate
         let cubit_file = feature_dir.join("cubits").join(format!("{}_cubit", snake_name)).join(format!("{}_cubit.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/cubits/feature_cubit/feature_cubit.dart.tmpl",
             &cubit_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- State Management: {}", cubit_file.display()));
         
         // Create state file
         let state_file = feature_dir.join("cubits").join(format!("{}_cubit", snake_name)).join(format!("{}_state.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/cubits/feature_cubit/feature_state.dart.tmpl",
             &state_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- State: {}", state_file.display()));
     }
@@ -201,20 +253,22 @@ fn generate_feature_files(
     // Generate repository if needed
     if params.has_repository {
         let repo_file = feature_dir.join("data/repository").join(format!("{}_repository.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/data/repository/feature_repository.dart.tmpl",
             &repo_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- Repository: {}", repo_file.display()));
         
         // Create model if needed
         if params.has_models {
             let model_file = feature_dir.join("data/models").join(format!("{}_model.dart", snake_name));
-            copy_template_file(
+            generate_template_file(
                 "features/common/data/models/feature_model.dart.tmpl",
                 &model_file,
-                &replacements
+                context,
+                false
             )?;
             created_files.push(format!("- Model: {}", model_file.display()));
         }
@@ -224,19 +278,21 @@ fn generate_feature_files(
     if params.has_pages {
         // Create page
         let page_file = feature_dir.join("ui/pages").join(format!("{}_page.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/ui/pages/feature_page.dart.tmpl",
             &page_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- UI Page: {}", page_file.display()));
         
         // Create widget
         let widget_file = feature_dir.join("ui/_widgets").join(format!("{}_item_widget.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/ui/_widgets/feature_item_widget.dart.tmpl",
             &widget_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- UI Widget: {}", widget_file.display()));
     }
@@ -244,10 +300,11 @@ fn generate_feature_files(
     // Generate router if needed
     if params.needs_routing {
         let router_file = feature_dir.join("router.dart");
-        copy_template_file(
+        generate_template_file(
             "features/common/router.dart.tmpl",
             &router_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- Router: {}", router_file.display()));
         
@@ -259,10 +316,11 @@ fn generate_feature_files(
     // Generate services if needed
     if params.has_services {
         let service_file = feature_dir.join("services").join(format!("{}_service.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/services/feature_service.dart.tmpl",
             &service_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- Service: {}", service_file.display()));
     }
@@ -270,10 +328,11 @@ fn generate_feature_files(
     // Generate utils if needed
     if params.has_utils {
         let utils_file = feature_dir.join("utils").join(format!("{}_helpers.dart", snake_name));
-        copy_template_file(
+        generate_template_file(
             "features/common/utils/feature_helpers.dart.tmpl",
             &utils_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- Utils: {}", utils_file.display()));
     }
@@ -281,10 +340,11 @@ fn generate_feature_files(
     // Generate DI if needed
     if params.needs_di {
         let di_file = feature_dir.join("di.dart");
-        copy_template_file(
+        generate_template_file(
             "features/common/di.dart.tmpl",
             &di_file,
-            &replacements
+            context,
+            false
         )?;
         created_files.push(format!("- DI: {}", di_file.display()));
         
@@ -308,12 +368,12 @@ fn generate_feature_files(
 pub fn update_main_router(project_dir: &Path, feature_name: &str, pascal_name: &str) -> Result<()> {
     let router_file_path = project_dir.join("lib/router.dart");
     
-    if !router_file_path.exists() {
+    if !vfs::exists(&router_file_path) {
         println!("Main router.dart not found at {:?}, skipping router integration", router_file_path);
         return Ok(());
     }
     
-    let mut content = std::fs::read_to_string(&router_file_path)
+    let mut content = vfs::read_to_string(&router_file_path)
         .context("Failed to read router.dart")?;
     
     // Add import if not already present
@@ -342,7 +402,7 @@ pub fn update_main_router(project_dir: &Path, feature_name: &str, pascal_name: &
     }
     
     // Write the updated content back to the file
-    std::fs::write(&router_file_path, content)
+    vfs::write(&router_file_path, content)
         .context("Failed to write updated router.dart")?;
     
     println!("✅ Updated main router.dart with {} routes", pascal_name);
@@ -354,12 +414,12 @@ pub fn update_main_router(project_dir: &Path, feature_name: &str, pascal_name: &
 pub fn update_main_di(project_dir: &Path, feature_name: &str) -> Result<()> {
     let di_file_path = project_dir.join("lib/di.dart");
     
-    if !di_file_path.exists() {
+    if !vfs::exists(&di_file_path) {
         println!("Main di.dart not found at {:?}, skipping DI integration", di_file_path);
         return Ok(());
     }
     
-    let mut content = std::fs::read_to_string(&di_file_path)
+    let mut content = vfs::read_to_string(&di_file_path)
         .context("Failed to read di.dart")?;
     
     // Add import if not already present
@@ -382,7 +442,7 @@ pub fn update_main_di(project_dir: &Path, feature_name: &str) -> Result<()> {
             
             // Add the new DI setup call
             let di_line = format!("\n  // Register {} dependencies\n  register{}Dependencies();", 
-                feature_name.replace("_", " ").to_string(), 
+                feature_name.replace("_", " "), 
                 feature_name.split('_')
                     .map(|word| word.chars().next().unwrap_or_default().to_uppercase().to_string() + &word[1..])
                     .collect::<String>());
@@ -394,7 +454,7 @@ pub fn update_main_di(project_dir: &Path, feature_name: &str) -> Result<()> {
     }
     
     // Write the updated content back to the file
-    std::fs::write(&di_file_path, content)
+    vfs::write(&di_file_path, content)
         .context("Failed to write updated di.dart")?;
     
     println!("✅ Updated main di.dart with {} dependencies", feature_name);
@@ -1,11 +1,18 @@
 // features/notifications.rs
 // Notifications feature generator
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use anyhow::{Context, Result};
-use std::fs;
+use crate::dependencies::{self, Packages};
+use crate::vfs;
 use crate::features::{FeatureParams, create_feature, update_main_router, update_main_di};
 
+/// Packages the specialized files of the notifications feature import
+pub const PACKAGES: Packages = Packages {
+    dependencies: &["bloc", "dartz", "equatable", "firebase_messaging", "flutter_bloc", "flutter_local_notifications", "get_it", "go_router", "injectable", "intl", "json_annotation", "provider"],
+    dev_dependencies: &["build_runner", "injectable_generator", "json_serializable"],
+};
+
 /// Create a notifications feature with required components
 pub fn create_notification_feature(project_dir: &Path) -> Result<()> {
     // Notification feature has specialized implementation for handling
@@ -14,7 +21,7 @@ pub fn create_notification_feature(project_dir: &Path) -> Result<()> {
     
     // Check if the feature already exists
     let feature_dir = project_dir.join("lib/features/notifications");
-    if feature_dir.exists() {
+    if vfs::exists(&feature_dir) {
         return Err(anyhow::anyhow!("Feature 'notifications' already exists at {:?}", feature_dir));
     }
     
@@ -25,12 +32,13 @@ pub fn create_notification_feature(project_dir: &Path) -> Result<()> {
     
     // Standard feature creation
     create_feature(project_dir, params)?;
+    dependencies::add_to_project(project_dir, &[PACKAGES])?;
     
     // Create notifications feature directory
     let feature_dir = project_dir.join("lib/features/notifications");
     
     // Create notification services directory
-    fs::create_dir_all(feature_dir.join("services"))
+    vfs::create_dir_all(feature_dir.join("services"))
         .context("Failed to create notification services directory")?;
     
     // Create notification service files
@@ -43,7 +51,7 @@ pub fn create_notification_feature(project_dir: &Path) -> Result<()> {
     
     for file in service_files.iter() {
         let service_path = feature_dir.join("services").join(file);
-        std::fs::write(&service_path, "// TODO: Implement notification service\n")
+        vfs::write(&service_path, "// TODO: Implement notification service\n")
             .context(format!("Failed to create service file: {}", file))?;
         created_files.push(format!("- Service: {}", service_path.display()));
     }
@@ -52,17 +60,17 @@ pub fn create_notification_feature(project_dir: &Path) -> Result<()> {
     let pages = ["notification_page.dart", "notification_details_page.dart"];
     for page in pages.iter() {
         let page_path = feature_dir.join("ui/pages").join(page);
-        std::fs::write(&page_path, "// TODO: Implement notification page\n")
+        vfs::write(&page_path, "// TODO: Implement notification page\n")
             .context(format!("Failed to create page file: {}", page))?;
         created_files.push(format!("- UI Page: {}", page_path.display()));
     }
     
     // Create notification model
-    fs::create_dir_all(feature_dir.join("data/models"))
+    vfs::create_dir_all(feature_dir.join("data/models"))
         .context("Failed to create notification models directory")?;
     
     let model_path = feature_dir.join("data/models/notification_model.dart");
-    std::fs::write(&model_path, 
+    vfs::write(&model_path, 
         "class NotificationModel {}\n")
         .context("Failed to create notification model")?;
     created_files.push(format!("- Model: {}", model_path.display()));
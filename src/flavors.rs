@@ -0,0 +1,144 @@
+// flavors.rs
+// Per-flavor files of a project, and adding or removing flavors after creation
+
+mod android;
+mod ios;
+
+use std::path::Path;
+use anyhow::{Context, Result};
+
+use crate::config::{self, FlavorConfig};
+use crate::manifest::{ProjectManifest, MANIFEST_FILE};
+use crate::templates::{FlavorContext, ProjectContext, TemplateContext};
+use crate::utils::{self, remove_generated_file, write_generated_file};
+use crate::vfs;
+
+/// Files generated for one flavor, as (template, path in the project)
+fn flavor_files(flavor: &str) -> [(&'static str, String); 2] {
+    [
+        ("common/main_flavor.dart.tmpl", format!("lib/main_{}.dart", flavor)),
+        ("assets/firebase/firebase-config.js.tmpl", format!("assets/firebase/{}/firebase-config.js", flavor)),
+    ]
+}
+
+/// Write the entry point and placeholder Firebase config of a flavor
+pub fn create_flavor_files(project_dir: &Path, project: &ProjectContext, flavor: &FlavorContext, force: bool) -> Result<()> {
+    let context = TemplateContext::for_flavor(project, flavor);
+    for (template, path) in flavor_files(&flavor.name) {
+        let content = utils::render_template(template, &context)?;
+        write_generated_file(&project_dir.join(path), &content, force)?;
+    }
+    Ok(())
+}
+
+/// Write the files listing every flavor: the `Flavor` enum and the native
+/// Android and iOS flavor setup
+pub fn write_flavor_list(project_dir: &Path, project: &ProjectContext, force: bool) -> Result<()> {
+    let flavors = utils::render_template("common/flavors.dart", &TemplateContext::for_project(project))?;
+    write_generated_file(&project_dir.join("lib/flavors.dart"), &flavors, force)?;
+
+    android::write_product_flavors(project_dir, project)?;
+    ios::write_flavors(project_dir, project)
+}
+
+/// The manifest of a project whose flavors are changed
+fn load_manifest(project_dir: &Path) -> Result<ProjectManifest> {
+    ProjectManifest::load(project_dir)?.with_context(|| {
+        format!(
+            "No {} found in {}: flavors can only be changed in projects generated by flutter_lazy",
+            MANIFEST_FILE,
+            project_dir.display()
+        )
+    })
+}
+
+/// Add a flavor to an existing project, overwriting hand edits of its files with `force`
+pub fn add_flavor(project_dir: &Path, flavor: FlavorConfig, force: bool) -> Result<()> {
+    let mut manifest = load_manifest(project_dir)?;
+    if manifest.flavors.iter().any(|existing| existing.name == flavor.name) {
+        return Err(anyhow::anyhow!("Flavor '{}' already exists", flavor.name));
+    }
+    manifest.flavors.push(flavor);
+    config::validate_flavors(&manifest.flavors)?;
+
+    let project = ProjectContext::from(&manifest);
+    let added = project.flavors.last().context("Flavor was not added")?;
+    create_flavor_files(project_dir, &project, added, force)?;
+    write_flavor_list(project_dir, &project, force)?;
+    manifest.save(project_dir)
+}
+
+/// Remove a flavor from an existing project, refusing to delete files edited by hand unless `force` is set
+pub fn remove_flavor(project_dir: &Path, name: &str, force: bool) -> Result<()> {
+    let mut manifest = load_manifest(project_dir)?;
+    let count = manifest.flavors.len();
+    manifest.flavors.retain(|flavor| flavor.name != name);
+    if manifest.flavors.len() == count {
+        return Err(anyhow::anyhow!("Flavor '{}' does not exist", name));
+    }
+    if manifest.flavors.is_empty() {
+        return Err(anyhow::anyhow!("Cannot remove '{}': it is the project's only flavor", name));
+    }
+
+    for (_, path) in flavor_files(name) {
+        let path = project_dir.join(path);
+        if vfs::exists(&path) {
+            remove_generated_file(&path, force)?;
+        }
+    }
+    ios::remove_flavor(project_dir, name)?;
+
+    write_flavor_list(project_dir, &ProjectContext::from(&manifest), force)?;
+    manifest.save(project_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_add_and_remove_flavor() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(&dir).unwrap();
+        let flavors = [FlavorConfig::named("dev"), FlavorConfig::named("prod")];
+        ProjectManifest::new("shop", "com.acme.shop", &flavors, &[]).save(&dir).unwrap();
+        fs::create_dir_all(dir.join("android/app")).unwrap();
+        fs::write(dir.join("android/app/build.gradle"), "android {\n    namespace \"com.acme.shop\"\n}\n").unwrap();
+
+        let qa = FlavorConfig {
+            base_url: Some("https://qa.acme.com".into()),
+            ..FlavorConfig::named("qa")
+        };
+        add_flavor(&dir, qa.clone(), false).unwrap();
+        assert!(add_flavor(&dir, qa, false).is_err());
+
+        let main = fs::read_to_string(dir.join("lib/main_qa.dart")).unwrap();
+        assert!(main.contains("F.appFlavor = Flavor.qa;"));
+        let enum_file = fs::read_to_string(dir.join("lib/flavors.dart")).unwrap();
+        assert!(enum_file.contains("  prod,\n  qa,\n}"), "{}", enum_file);
+        assert!(enum_file.contains("return 'https://qa.acme.com';"));
+        assert!(dir.join("assets/firebase/qa/firebase-config.js").exists());
+        let gradle = fs::read_to_string(dir.join("android/app/build.gradle")).unwrap();
+        assert!(gradle.contains("        qa {\n            dimension \"flavor\"\n            applicationIdSuffix \".qa\"\n            resValue \"string\", \"app_name\", \"Shop Qa\"\n        }"), "{}", gradle);
+        assert!(!gradle.contains("applicationIdSuffix \".prod\""));
+
+        // Hand-edited flavor files are kept unless forced
+        fs::write(dir.join("lib/main_qa.dart"), format!("{}// edited\n", main)).unwrap();
+        assert!(remove_flavor(&dir, "qa", false).is_err());
+
+        remove_flavor(&dir, "qa", true).unwrap();
+        assert!(!dir.join("lib/main_qa.dart").exists());
+        assert!(!dir.join("assets/firebase/qa/firebase-config.js").exists());
+        let enum_file = fs::read_to_string(dir.join("lib/flavors.dart")).unwrap();
+        assert!(!enum_file.contains("qa"));
+        assert!(!fs::read_to_string(dir.join("android/app/build.gradle")).unwrap().contains("qa"));
+        let manifest = ProjectManifest::load(&dir).unwrap().unwrap();
+        assert_eq!(manifest.flavors, flavors);
+
+        remove_flavor(&dir, "prod", false).unwrap();
+        assert!(remove_flavor(&dir, "dev", false).is_err());
+        assert!(remove_flavor(&dir, "stage", false).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+// android.rs
+// Android productFlavors generated into the app's Gradle build file
+
+use std::path::Path;
+use anyhow::{Context, Result};
+
+use crate::templates::{ProjectContext, TemplateContext};
+use crate::utils;
+use crate::vfs;
+
+/// Marker opening the generated productFlavors block
+const BLOCK_START: &str = "// flutter_lazy:flavors-start";
+
+/// Marker closing the generated productFlavors block
+const BLOCK_END: &str = "// flutter_lazy:flavors-end";
+
+/// Write the productFlavors of every flavor into android/app/build.gradle(.kts)
+pub fn write_product_flavors(project_dir: &Path, project: &ProjectContext) -> Result<()> {
+    let app_dir = project_dir.join("android/app");
+    let (build_file, template) = if vfs::exists(app_dir.join("build.gradle.kts")) {
+        (app_dir.join("build.gradle.kts"), "flavors/android/product_flavors.gradle.kts")
+    } else if vfs::exists(app_dir.join("build.gradle")) {
+        (app_dir.join("build.gradle"), "flavors/android/product_flavors.gradle")
+    } else {
+        println!("Skipping Android flavors: android/app has no Gradle build file");
+        return Ok(());
+    };
+
+    let block = utils::render_template(template, &TemplateContext::for_project(project))?;
+    let content = vfs::read_to_string(&build_file)
+        .with_context(|| format!("Failed to read {}", build_file.display()))?;
+    let content = replace_block(&content, &block)
+        .with_context(|| format!("Failed to add flavors to {}", build_file.display()))?;
+    vfs::write(&build_file, content)
+        .with_context(|| format!("Failed to write {}", build_file.display()))?;
+
+    // Label the app with the app_name each flavor defines
+    let manifest_path = app_dir.join("src/main/AndroidManifest.xml");
+    if vfs::exists(&manifest_path) {
+        let manifest = vfs::read_to_string(&manifest_path).context("Failed to read AndroidManifest.xml")?;
+        vfs::write(&manifest_path, use_app_name_label(&manifest))
+            .context("Failed to write AndroidManifest.xml")?;
+    }
+
+    Ok(())
+}
+
+/// Replace the generated block of a Gradle file, or add it at the top of `android {`
+fn replace_block(content: &str, block: &str) -> Result<String> {
+    let mut block = block.to_string();
+    if !block.ends_with('\n') {
+        block.push('\n');
+    }
+
+    if let (Some(start), Some(end)) = (content.find(BLOCK_START), content.find(BLOCK_END)) {
+        let start = content[..start].rfind('\n').map_or(0, |newline| newline + 1);
+        let end = end + BLOCK_END.len();
+        let end = content[end..].find('\n').map_or(content.len(), |newline| end + newline + 1);
+        return Ok(format!("{}{}{}", &content[..start], block, &content[end..]));
+    }
+
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim() == "android {" {
+            return Ok(format!("{}{}{}", &content[..offset], block, &content[offset..]));
+        }
+    }
+    Err(anyhow::anyhow!("No `android {{` block found"))
+}
+
+/// Point the application label at the `app_name` resource
+fn use_app_name_label(manifest: &str) -> String {
+    const LABEL: &str = "android:label=\"";
+    let Some(start) = manifest.find(LABEL).map(|index| index + LABEL.len()) else {
+        return manifest.to_string();
+    };
+    let Some(length) = manifest[start..].find('"') else {
+        return manifest.to_string();
+    };
+    format!("{}@string/app_name{}", &manifest[..start], &manifest[start + length..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_product_flavors_block() {
+        let gradle = "plugins {\n}\n\nandroid {\n    namespace \"com.acme.shop\"\n}\n";
+        let block = "    // flutter_lazy:flavors-start\n    productFlavors { dev {} }\n    // flutter_lazy:flavors-end\n";
+        let added = replace_block(gradle, block).unwrap();
+        assert_eq!(added, format!("plugins {{\n}}\n\nandroid {{\n{}    namespace \"com.acme.shop\"\n}}\n", block));
+
+        // Regenerating replaces the block instead of adding another
+        let replaced = replace_block(&added, &block.replace("dev", "qa")).unwrap();
+        assert_eq!(replaced, added.replace("dev", "qa"));
+        assert!(replace_block("plugins {\n}\n", block).is_err());
+
+        let manifest = "<application\n        android:label=\"shop\"\n        android:name=\"${applicationName}\">";
+        assert_eq!(
+            use_app_name_label(manifest),
+            "<application\n        android:label=\"@string/app_name\"\n        android:name=\"${applicationName}\">"
+        );
+    }
+}
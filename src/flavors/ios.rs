@@ -0,0 +1,496 @@
+// ios.rs
+// iOS build configurations, xcconfig files and schemes for each flavor
+
+use std::ops::Range;
+use std::path::Path;
+use anyhow::{Context, Result};
+
+use crate::templates::{self, FlavorContext, ProjectContext};
+use crate::vfs;
+
+/// Xcode project of the Runner app
+const PROJECT_FILE: &str = "ios/Runner.xcodeproj/project.pbxproj";
+
+/// Shared schemes of the Runner project; flutter expects one named after each flavor
+const SCHEMES_DIR: &str = "ios/Runner.xcodeproj/xcshareddata/xcschemes";
+
+/// Build configurations every flavor gets a copy of, with the Flutter xcconfig they build on
+const BUILDS: [(&str, &str); 3] = [("Debug", "Debug"), ("Release", "Release"), ("Profile", "Release")];
+
+/// Build configuration of a flavor, named the way `flutter run --flavor` looks it up
+fn config_name(build: &str, flavor: &str) -> String {
+    format!("{}-{}", build, flavor)
+}
+
+/// A 24 digit object id that stays the same for the same parts, so entries
+/// added for a flavor can be found again when it is removed
+fn object_id(parts: &[&str]) -> String {
+    let hash = |seed: u64| {
+        parts.join("/").bytes().fold(seed, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+    };
+    let high = hash(0xcbf29ce484222325);
+    let low = hash(0x84222325cbf29ce4);
+    format!("{:016X}{:08X}", high, low as u32)
+}
+
+/// Id of a flavor's copy of a build configuration
+fn flavor_config_id(flavor: &str, base_id: &str) -> String {
+    object_id(&["flutter_lazy", flavor, base_id])
+}
+
+/// Id of the file reference to a flavor's xcconfig
+fn xcconfig_id(flavor: &str, build: &str) -> String {
+    object_id(&["flutter_lazy", flavor, build, "xcconfig"])
+}
+
+/// Write the xcconfig files and schemes of every flavor and add their build
+/// configurations to the Xcode project
+pub fn write_flavors(project_dir: &Path, project: &ProjectContext) -> Result<()> {
+    let project_path = project_dir.join(PROJECT_FILE);
+    if !vfs::exists(&project_path) {
+        println!("Skipping iOS flavors: {} not found", PROJECT_FILE);
+        return Ok(());
+    }
+
+    let mut pbxproj = vfs::read_to_string(&project_path).context("Failed to read the Xcode project")?;
+    for flavor in &project.flavors {
+        for (build, base) in BUILDS {
+            let path = project_dir.join(format!("ios/Flutter/{}.xcconfig", config_name(build, &flavor.name)));
+            vfs::write(&path, xcconfig(flavor, base))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        pbxproj = add_build_configurations(&pbxproj, &flavor.name)?;
+        write_scheme(project_dir, &flavor.name)?;
+    }
+    pbxproj = set_default_display_name(&pbxproj, &project.name.title);
+    vfs::write(&project_path, pbxproj).context("Failed to write the Xcode project")?;
+
+    // Show the app name each flavor's xcconfig sets
+    let info_path = project_dir.join("ios/Runner/Info.plist");
+    if vfs::exists(&info_path) {
+        let info = vfs::read_to_string(&info_path).context("Failed to read Info.plist")?;
+        vfs::write(&info_path, use_display_name_setting(&info)).context("Failed to write Info.plist")?;
+    }
+
+    write_podfile_configurations(project_dir, project)
+}
+
+/// Tell CocoaPods which flavor configurations are debug builds, or it treats them as release
+fn write_podfile_configurations(project_dir: &Path, project: &ProjectContext) -> Result<()> {
+    let podfile_path = project_dir.join("ios/Podfile");
+    if !vfs::exists(&podfile_path) {
+        return Ok(());
+    }
+
+    let podfile = vfs::read_to_string(&podfile_path).context("Failed to read the Podfile")?;
+    let flavors: Vec<&str> = project.flavors.iter().map(|flavor| flavor.name.as_str()).collect();
+    if let Some(podfile) = podfile_configurations(&podfile, &flavors) {
+        vfs::write(&podfile_path, podfile).context("Failed to write the Podfile")?;
+    }
+    Ok(())
+}
+
+/// The Podfile with its `project 'Runner'` mapping listing every flavor's configurations
+fn podfile_configurations(podfile: &str, flavors: &[&str]) -> Option<String> {
+    let start = podfile.find("project 'Runner', {\n")? + "project 'Runner', {\n".len();
+    let end = start + podfile[start..].find("}\n")?;
+
+    let mut mapping = String::new();
+    for flavor in std::iter::once(None).chain(flavors.iter().map(Some)) {
+        for (build, _) in BUILDS {
+            let name = flavor.map_or(build.to_string(), |flavor| config_name(build, flavor));
+            let kind = if build == "Debug" { "debug" } else { "release" };
+            mapping.push_str(&format!("  '{}' => :{},\n", name, kind));
+        }
+    }
+    Some(format!("{}{}{}", &podfile[..start], mapping, &podfile[end..]))
+}
+
+/// Remove the xcconfig files, scheme and build configurations of a flavor
+pub fn remove_flavor(project_dir: &Path, flavor: &str) -> Result<()> {
+    let project_path = project_dir.join(PROJECT_FILE);
+    if !vfs::exists(&project_path) {
+        return Ok(());
+    }
+
+    let mut pbxproj = vfs::read_to_string(&project_path).context("Failed to read the Xcode project")?;
+    for list in configuration_lists(&pbxproj) {
+        for (base_id, _) in base_configs(&list) {
+            pbxproj = remove_object(&pbxproj, &flavor_config_id(flavor, &base_id));
+        }
+    }
+    for (build, _) in BUILDS {
+        pbxproj = remove_object(&pbxproj, &xcconfig_id(flavor, build));
+
+        let path = project_dir.join(format!("ios/Flutter/{}.xcconfig", config_name(build, flavor)));
+        if vfs::exists(&path) {
+            vfs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+    vfs::write(&project_path, pbxproj).context("Failed to write the Xcode project")?;
+
+    let scheme_path = project_dir.join(SCHEMES_DIR).join(format!("{}.xcscheme", flavor));
+    if vfs::exists(&scheme_path) {
+        vfs::remove_file(&scheme_path).with_context(|| format!("Failed to remove {}", scheme_path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Settings of a flavor on top of the Flutter xcconfig of its build
+fn xcconfig(flavor: &FlavorContext, base: &str) -> String {
+    format!(
+        "// Generated by flutter_lazy for the {} flavor\n#include \"{}.xcconfig\"\n\nBUNDLE_ID_SUFFIX = {}\nAPP_DISPLAY_NAME = {}\n",
+        flavor.name, base, flavor.suffix, templates::xcconfig_value(&flavor.display_name)
+    )
+}
+
+/// A scheme for the flavor, copied from the Runner scheme with the flavor's build configurations
+fn write_scheme(project_dir: &Path, flavor: &str) -> Result<()> {
+    let runner_path = project_dir.join(SCHEMES_DIR).join("Runner.xcscheme");
+    if !vfs::exists(&runner_path) {
+        println!("Skipping the {} scheme: Runner.xcscheme not found", flavor);
+        return Ok(());
+    }
+
+    let mut scheme = vfs::read_to_string(&runner_path).context("Failed to read Runner.xcscheme")?;
+    for (build, _) in BUILDS {
+        scheme = scheme.replace(
+            &format!("buildConfiguration = \"{}\"", build),
+            &format!("buildConfiguration = \"{}\"", config_name(build, flavor)),
+        );
+    }
+
+    let path = project_dir.join(SCHEMES_DIR).join(format!("{}.xcscheme", flavor));
+    vfs::write(&path, scheme).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A build configuration list of the Xcode project
+struct ConfigurationList {
+    id: String,
+    /// Whether the list belongs to the Runner app target, whose bundle id flavors change
+    is_runner: bool,
+    /// Build configurations as (id, name)
+    configs: Vec<(String, String)>,
+}
+
+/// Debug, Release and Profile configurations of a list, which flavors copy
+fn base_configs(list: &ConfigurationList) -> Vec<(String, String)> {
+    list.configs
+        .iter()
+        .filter(|(_, name)| BUILDS.iter().any(|(build, _)| build == name))
+        .cloned()
+        .collect()
+}
+
+/// Every build configuration list of the project
+fn configuration_lists(pbxproj: &str) -> Vec<ConfigurationList> {
+    let mut lists = Vec::new();
+    let mut current: Option<ConfigurationList> = None;
+
+    for line in pbxproj.lines() {
+        if let Some(list) = &mut current {
+            let entry = line.trim();
+            if entry == "};" {
+                lists.extend(current.take());
+            } else if let Some((id, rest)) = entry.split_once(" /* ") {
+                if let Some(name) = rest.strip_suffix(" */,") {
+                    list.configs.push((id.to_string(), name.trim_matches('"').to_string()));
+                }
+            }
+        } else if line.contains("/* Build configuration list for ") && line.ends_with(" = {") {
+            let id = line.trim().split(' ').next().unwrap_or_default().to_string();
+            current = Some(ConfigurationList {
+                id,
+                is_runner: line.contains("PBXNativeTarget \"Runner\""),
+                configs: Vec::new(),
+            });
+        }
+    }
+
+    lists
+}
+
+/// Byte range of an object's definition, including its trailing newline
+fn object_range(pbxproj: &str, id: &str) -> Option<Range<usize>> {
+    let start = pbxproj.find(&format!("\n\t\t{} ", id))? + 1;
+    let line_end = pbxproj[start..].find('\n').map_or(pbxproj.len(), |newline| start + newline + 1);
+    if pbxproj[start..line_end].trim_end().ends_with("};") {
+        return Some(start..line_end);
+    }
+
+    let end = pbxproj[start..].find("\n\t\t};\n")? + start + "\n\t\t};\n".len();
+    Some(start..end)
+}
+
+/// Insert lines after an object's definition
+fn insert_after(pbxproj: &str, id: &str, lines: &str) -> Option<String> {
+    let range = object_range(pbxproj, id)?;
+    Some(format!("{}{}{}", &pbxproj[..range.end], lines, &pbxproj[range.end..]))
+}
+
+/// Add a child to a list property (`buildConfigurations = (` or `children = (`) of an object
+fn add_to_list(pbxproj: &str, id: &str, property: &str, child: &str) -> Option<String> {
+    let range = object_range(pbxproj, id)?;
+    let object = &pbxproj[range.clone()];
+    let list_start = object.find(&format!("{} = (", property))?;
+    let list_end = list_start + object[list_start..].find("\t\t\t);")?;
+    let at = range.start + list_end;
+    Some(format!("{}\t\t\t\t{},\n{}", &pbxproj[..at], child, &pbxproj[at..]))
+}
+
+/// Remove an object's definition and every reference to it
+fn remove_object(pbxproj: &str, id: &str) -> String {
+    let mut pbxproj = pbxproj.to_string();
+    if let Some(range) = object_range(&pbxproj, id) {
+        pbxproj.replace_range(range, "");
+    }
+    pbxproj
+        .split_inclusive('\n')
+        .filter(|line| !line.contains(id))
+        .collect()
+}
+
+/// Copy the Debug, Release and Profile configurations of every list for a flavor
+///
+/// Copies of the Runner target build on the flavor's xcconfig and add its
+/// bundle id suffix. Configurations the flavor already has are left alone.
+fn add_build_configurations(pbxproj: &str, flavor: &str) -> Result<String> {
+    let mut pbxproj = pbxproj.to_string();
+
+    for list in configuration_lists(&pbxproj) {
+        for (base_id, build) in base_configs(&list) {
+            let id = flavor_config_id(flavor, &base_id);
+            if object_range(&pbxproj, &id).is_some() {
+                continue;
+            }
+            let name = config_name(&build, flavor);
+            let base_range = object_range(&pbxproj, &base_id)
+                .with_context(|| format!("Build configuration {} not found in the Xcode project", base_id))?;
+
+            let mut config = String::new();
+            for line in pbxproj[base_range].split_inclusive('\n') {
+                let setting = line.trim_start();
+                if config.is_empty() {
+                    config.push_str(&format!("\t\t{} /* {} */ = {{\n", id, name));
+                } else if setting.starts_with("name = ") {
+                    config.push_str(&format!("\t\t\tname = \"{}\";\n", name));
+                } else if list.is_runner && setting.starts_with("baseConfigurationReference = ") {
+                    continue;
+                } else if list.is_runner && setting.starts_with("isa = XCBuildConfiguration;") {
+                    config.push_str(line);
+                    config.push_str(&format!(
+                        "\t\t\tbaseConfigurationReference = {} /* {}.xcconfig */;\n",
+                        xcconfig_id(flavor, &build), name
+                    ));
+                } else if list.is_runner && setting.starts_with("APP_DISPLAY_NAME = ") {
+                    // Left to the flavor's xcconfig
+                    continue;
+                } else if let Some(bundle_id) = setting.strip_prefix("PRODUCT_BUNDLE_IDENTIFIER = ").filter(|_| list.is_runner) {
+                    let bundle_id = bundle_id.trim_end().trim_end_matches(';').trim_matches('"');
+                    config.push_str(&format!("\t\t\t\tPRODUCT_BUNDLE_IDENTIFIER = \"{}$(BUNDLE_ID_SUFFIX)\";\n", bundle_id));
+                } else {
+                    config.push_str(line);
+                }
+            }
+
+            pbxproj = insert_after(&pbxproj, &base_id, &config).context("Failed to add a build configuration")?;
+            pbxproj = add_to_list(&pbxproj, &list.id, "buildConfigurations", &format!("{} /* {} */", id, name))
+                .context("Failed to add a build configuration")?;
+
+            if list.is_runner {
+                pbxproj = add_xcconfig_reference(&pbxproj, &xcconfig_id(flavor, &build), &name)?;
+            }
+        }
+    }
+
+    Ok(pbxproj)
+}
+
+/// Reference an xcconfig file from the project, next to Flutter's own xcconfigs
+fn add_xcconfig_reference(pbxproj: &str, id: &str, name: &str) -> Result<String> {
+    let reference = format!(
+        "\t\t{0} /* {1}.xcconfig */ = {{isa = PBXFileReference; lastKnownFileType = text.xcconfig; name = \"{1}.xcconfig\"; path = \"Flutter/{1}.xcconfig\"; sourceTree = \"<group>\"; }};\n",
+        id, name
+    );
+    let section_end = pbxproj.find("/* End PBXFileReference section */")
+        .context("No file references found in the Xcode project")?;
+    let pbxproj = format!("{}{}{}", &pbxproj[..section_end], reference, &pbxproj[section_end..]);
+
+    let group_id = pbxproj
+        .lines()
+        .find(|line| line.starts_with("\t\t") && line.ends_with(" /* Flutter */ = {"))
+        .and_then(|line| line.split_whitespace().next())
+        .map(str::to_string);
+    Ok(match group_id {
+        Some(group_id) => add_to_list(&pbxproj, &group_id, "children", &format!("{} /* {}.xcconfig */", id, name))
+            .unwrap_or(pbxproj),
+        None => pbxproj,
+    })
+}
+
+/// Give the Runner target's own configurations the project title as app name,
+/// for builds without a flavor
+fn set_default_display_name(pbxproj: &str, title: &str) -> String {
+    let mut pbxproj = pbxproj.to_string();
+    let runner_configs = configuration_lists(&pbxproj)
+        .into_iter()
+        .filter(|list| list.is_runner)
+        .flat_map(|list| base_configs(&list));
+
+    for (id, _) in runner_configs {
+        let Some(range) = object_range(&pbxproj, &id) else { continue };
+        let config = &pbxproj[range.clone()];
+        if config.contains("APP_DISPLAY_NAME = ") {
+            continue;
+        }
+        if let Some(settings) = config.find("buildSettings = {\n") {
+            let at = range.start + settings + "buildSettings = {\n".len();
+            let title = title.replace('\\', "\\\\").replace('"', "\\\"");
+            pbxproj.insert_str(at, &format!("\t\t\t\tAPP_DISPLAY_NAME = \"{}\";\n", title));
+        }
+    }
+
+    pbxproj
+}
+
+/// Take the display name from the `APP_DISPLAY_NAME` build setting
+fn use_display_name_setting(info: &str) -> String {
+    const KEY: &str = "<key>CFBundleDisplayName</key>";
+    let Some(key) = info.find(KEY) else {
+        return info.to_string();
+    };
+    let after_key = key + KEY.len();
+    let value = info[after_key..]
+        .find("<string>")
+        .zip(info[after_key..].find("</string>"))
+        .map(|(start, end)| (after_key + start + "<string>".len(), after_key + end));
+    match value {
+        Some((start, end)) if start <= end => format!("{}$(APP_DISPLAY_NAME){}", &info[..start], &info[end..]),
+        _ => info.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parts of a `flutter create` project file that flavors change
+    const PBXPROJ: &str = "// !$*UTF8*$!
+{
+	objects = {
+
+/* Begin PBXFileReference section */
+		7AFA3C8E1D35360C0083082E /* Release.xcconfig */ = {isa = PBXFileReference; lastKnownFileType = text.xcconfig; name = Release.xcconfig; path = Flutter/Release.xcconfig; sourceTree = \"<group>\"; };
+		9740EEB21CF90195004384FC /* Debug.xcconfig */ = {isa = PBXFileReference; lastKnownFileType = text.xcconfig; name = Debug.xcconfig; path = Flutter/Debug.xcconfig; sourceTree = \"<group>\"; };
+/* End PBXFileReference section */
+
+/* Begin PBXGroup section */
+		9740EEB11CF90186004384FC /* Flutter */ = {
+			isa = PBXGroup;
+			children = (
+				9740EEB21CF90195004384FC /* Debug.xcconfig */,
+				7AFA3C8E1D35360C0083082E /* Release.xcconfig */,
+			);
+			name = Flutter;
+			sourceTree = \"<group>\";
+		};
+/* End PBXGroup section */
+
+/* Begin XCBuildConfiguration section */
+		97C147031CF9000F007B8A3F /* Debug */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				SDKROOT = iphoneos;
+			};
+			name = Debug;
+		};
+		97C147061CF9000F007B8A3F /* Debug */ = {
+			isa = XCBuildConfiguration;
+			baseConfigurationReference = 9740EEB21CF90195004384FC /* Debug.xcconfig */;
+			buildSettings = {
+				PRODUCT_BUNDLE_IDENTIFIER = com.acme.shop;
+				PRODUCT_NAME = \"$(TARGET_NAME)\";
+			};
+			name = Debug;
+		};
+/* End XCBuildConfiguration section */
+
+/* Begin XCConfigurationList section */
+		97C146E91CF9000F007B8A3F /* Build configuration list for PBXProject \"Runner\" */ = {
+			isa = XCConfigurationList;
+			buildConfigurations = (
+				97C147031CF9000F007B8A3F /* Debug */,
+			);
+			defaultConfigurationIsVisible = 0;
+			defaultConfigurationName = Release;
+		};
+		97C147051CF9000F007B8A3F /* Build configuration list for PBXNativeTarget \"Runner\" */ = {
+			isa = XCConfigurationList;
+			buildConfigurations = (
+				97C147061CF9000F007B8A3F /* Debug */,
+			);
+			defaultConfigurationIsVisible = 0;
+			defaultConfigurationName = Release;
+		};
+/* End XCConfigurationList section */
+	};
+}
+";
+
+    #[test]
+    fn test_build_configurations() {
+        let added = add_build_configurations(PBXPROJ, "dev").unwrap();
+        assert_eq!(add_build_configurations(&added, "dev").unwrap(), added);
+
+        let lists = configuration_lists(&added);
+        assert_eq!(lists.len(), 2);
+        for list in &lists {
+            let names: Vec<&str> = list.configs.iter().map(|(_, name)| name.as_str()).collect();
+            assert_eq!(names, ["Debug", "Debug-dev"]);
+        }
+
+        let runner_id = flavor_config_id("dev", "97C147061CF9000F007B8A3F");
+        let runner = &added[object_range(&added, &runner_id).unwrap()];
+        assert!(runner.contains(&format!("baseConfigurationReference = {} /* Debug-dev.xcconfig */;", xcconfig_id("dev", "Debug"))));
+        assert!(runner.contains("PRODUCT_BUNDLE_IDENTIFIER = \"com.acme.shop$(BUNDLE_ID_SUFFIX)\";"));
+        assert!(runner.contains("name = \"Debug-dev\";"));
+        assert!(added.contains("path = \"Flutter/Debug-dev.xcconfig\""));
+        assert!(added.contains(&format!("\t\t\t\t{} /* Debug-dev.xcconfig */,\n\t\t\t);", xcconfig_id("dev", "Debug"))));
+
+        let named = set_default_display_name(&added, "Shop");
+        assert_eq!(named.matches("APP_DISPLAY_NAME = \"Shop\";").count(), 1);
+
+        // Removing the flavor restores the original project
+        let mut removed = added.clone();
+        for id in [
+            flavor_config_id("dev", "97C147031CF9000F007B8A3F"),
+            runner_id,
+            xcconfig_id("dev", "Debug"),
+        ] {
+            removed = remove_object(&removed, &id);
+        }
+        assert_eq!(removed, PBXPROJ);
+    }
+
+    #[test]
+    fn test_podfile_configurations() {
+        let podfile = "platform :ios, '12.0'\n\nproject 'Runner', {\n  'Debug' => :debug,\n  'Profile' => :release,\n  'Release' => :release,\n}\n";
+        let mapped = podfile_configurations(podfile, &["dev"]).unwrap();
+        assert_eq!(
+            mapped,
+            "platform :ios, '12.0'\n\nproject 'Runner', {\n  'Debug' => :debug,\n  'Release' => :release,\n  'Profile' => :release,\n  \
+             'Debug-dev' => :debug,\n  'Release-dev' => :release,\n  'Profile-dev' => :release,\n}\n"
+        );
+        assert!(podfile_configurations("platform :ios, '12.0'\n", &["dev"]).is_none());
+    }
+
+    #[test]
+    fn test_display_name_setting() {
+        let info = "<dict>\n\t<key>CFBundleDisplayName</key>\n\t<string>Shop</string>\n</dict>";
+        assert_eq!(
+            use_display_name_setting(info),
+            "<dict>\n\t<key>CFBundleDisplayName</key>\n\t<string>$(APP_DISPLAY_NAME)</string>\n</dict>"
+        );
+    }
+}
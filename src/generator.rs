@@ -1,22 +1,51 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::fs;
 use anyhow::{Context, Result};
 use dialoguer::{MultiSelect, Input, Select};
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
 use convert_case::{Case, Casing};
-use fs_extra::dir::CopyOptions;
 
-use crate::utils::copy_template_file;
+use crate::config::{self, FlavorConfig, AVAILABLE_FEATURES, DEFAULT_FLAVORS};
+use crate::dependencies::{self, Packages};
+use crate::utils::{copy_template_asset, copy_template_file};
+use crate::flavors;
 use crate::features::{create_auth_feature, create_notification_feature, create_main_page_feature};
+use crate::manifest::{ProjectManifest, MANIFEST_FILE};
+use crate::pubspec::Pubspec;
 use crate::swagger;
-use crate::validation::{ValidationSystem, ValidationRules};
+use crate::templates::{ProjectContext, TemplateContext};
+use crate::validation::{ValidationSystem, create_manifest_validation};
+use crate::vfs;
+
+/// Packages the project templates import; features add their own when generated
+pub const PROJECT_PACKAGES: Packages = Packages {
+    dependencies: &[
+        "dartz",
+        "dio",
+        "easy_localization",
+        "equatable",
+        "firebase_core",
+        "firebase_messaging",
+        "flutter_bloc",
+        "flutter_screenutil",
+        "formz",
+        "get_it",
+        "go_router",
+        "google_fonts",
+        "json_annotation",
+        "logger",
+        "pretty_dio_logger",
+        "shared_preferences",
+    ],
+    dev_dependencies: &["build_runner", "json_serializable"],
+};
 
 pub struct ProjectConfig {
     pub name: String,
     pub package_name: String,
-    pub flavors: Vec<String>,
+    pub flavors: Vec<FlavorConfig>,
     pub features: Vec<String>,
     pub output_dir: PathBuf,
 }
@@ -30,65 +59,112 @@ pub struct ApiSpec {
 pub struct FlutterProjectGenerator {
     config: ProjectConfig,
     api_spec: Option<ApiSpec>,
+    /// Project overrides of the API type mappings, keyed by `type` or `type:format`
+    type_mappings: BTreeMap<String, String>,
+    /// How API operations are grouped into features
+    grouping: swagger::GroupingStrategy,
+}
+
+/// Options for `new`; anything left as `None` is prompted for when
+/// `interactive` is set and defaulted otherwise
+pub struct NewProjectOptions {
+    pub name: String,
+    pub output: Option<PathBuf>,
+    pub package_name: Option<String>,
+    pub flavors: Option<Vec<FlavorConfig>>,
+    pub features: Option<Vec<String>>,
+    pub api_url: Option<String>,
+    pub api_file: Option<PathBuf>,
+    pub api_domains: Option<Vec<String>>,
+    pub api_type_mappings: BTreeMap<String, String>,
+    pub api_grouping: swagger::GroupingStrategy,
+    pub no_api: bool,
+    pub interactive: bool,
 }
 
 impl FlutterProjectGenerator {
-    pub fn new(name: &str, output: &Option<PathBuf>, package_name: &Option<String>,
-              api_url: &Option<String>, api_file: &Option<PathBuf>) -> Result<Self> {
-        let output_dir = output.clone().unwrap_or_else(|| PathBuf::from("."));
+    pub fn new(options: NewProjectOptions) -> Result<Self> {
+        let output_dir = options.output.clone().unwrap_or_else(|| PathBuf::from("."));
         
         // Convert project name to snake_case for directory
-        let project_dir_name = name.to_case(Case::Snake);
+        let project_dir_name = options.name.to_case(Case::Snake);
         
         // Determine package name: use provided or generate from project name
         let default_package_name = format!("com.example.{}", project_dir_name);
-        let package_name = package_name.clone().unwrap_or(default_package_name);
+        let package_name = options.package_name.clone().unwrap_or(default_package_name);
+        
+        if options.interactive {
+            println!("{}", style("Project Setup").bold().cyan());
+        }
         
         // Ask for flavors
-        println!("{}", style("Project Setup").bold().cyan());
-        let default_flavors = vec!["dev", "stage", "prod"];
-        let flavors = MultiSelect::new()
-            .with_prompt("Select flavors (Space to select, Enter to confirm)")
-            .items(&default_flavors)
-            .defaults(&[true, true, true])
-            .interact()?
-            .iter()
-            .map(|&i| default_flavors[i].to_string())
-            .collect();
+        let flavors = match &options.flavors {
+            Some(flavors) => flavors.clone(),
+            None if options.interactive => {
+                MultiSelect::new()
+                    .with_prompt("Select flavors (Space to select, Enter to confirm)")
+                    .items(&DEFAULT_FLAVORS)
+                    .defaults(&[true, true, true])
+                    .interact()?
+                    .iter()
+                    .map(|&i| FlavorConfig::named(DEFAULT_FLAVORS[i]))
+                    .collect()
+            },
+            None => DEFAULT_FLAVORS.iter().map(|f| FlavorConfig::named(f)).collect(),
+        };
+        config::validate_flavors(&flavors)?;
         
         // Ask for features
-        let available_features = vec!["auth", "notifications", "main_page"];
-        let selected_features = MultiSelect::new()
-            .with_prompt("Select features to include")
-            .items(&available_features)
-            .defaults(&[true, true, true, false])
-            .interact()?
-            .iter()
-            .map(|&i| available_features[i].to_string())
-            .collect();
+        let selected_features = match &options.features {
+            Some(features) => features.clone(),
+            None if options.interactive => {
+                MultiSelect::new()
+                    .with_prompt("Select features to include")
+                    .items(&AVAILABLE_FEATURES)
+                    .defaults(&[true, true, true])
+                    .interact()?
+                    .iter()
+                    .map(|&i| AVAILABLE_FEATURES[i].to_string())
+                    .collect()
+            },
+            None => AVAILABLE_FEATURES.iter().map(|f| f.to_string()).collect(),
+        };
+        config::validate_features(&selected_features)?;
 
         // Process API specification if provided
-        let api_spec = Self::process_api_spec(api_url, api_file)?;
+        let api_spec = if options.no_api {
+            None
+        } else {
+            Self::process_api_spec(&options.api_url, &options.api_file, &options.api_domains, options.interactive)?
+        };
         
         Ok(Self {
             config: ProjectConfig {
-                name: name.to_string(),
+                name: options.name,
                 package_name,
                 flavors,
                 features: selected_features,
                 output_dir: output_dir.join(project_dir_name),
             },
             api_spec,
+            type_mappings: options.api_type_mappings,
+            grouping: options.api_grouping,
         })
     }
     
     /// Process API specification from URL or file
     fn process_api_spec(
         api_url: &Option<String>, 
-        api_file: &Option<PathBuf>
+        api_file: &Option<PathBuf>,
+        domains: &Option<Vec<String>>,
+        interactive: bool,
     ) -> Result<Option<ApiSpec>> {
         // If no API source is provided, ask if user wants to include an API spec
         if api_url.is_none() && api_file.is_none() {
+            if !interactive {
+                return Ok(None);
+            }
+            
             let include_api = Select::new()
                 .with_prompt("Would you like to generate features from a Swagger/OpenAPI specification?")
                 .items(&["No", "Yes, from URL", "Yes, from local file"])
@@ -103,22 +179,24 @@ impl FlutterProjectGenerator {
             if include_api == 1 {
                 // User chose to include an API spec from URL
                 let url = Self::prompt_for_api_url(None)?;
-                return Self::create_api_spec(Some(url), None);
+                return Self::create_api_spec(Some(url), None, domains, interactive);
             } else {
                 // User chose to include an API spec from file
                 let file_path = Self::prompt_for_api_file(None)?;
-                return Self::create_api_spec(None, Some(file_path));
+                return Self::create_api_spec(None, Some(file_path), domains, interactive);
             }
         }
         
         // Process provided API URL or file
-        Self::create_api_spec(api_url.clone(), api_file.clone())
+        Self::create_api_spec(api_url.clone(), api_file.clone(), domains, interactive)
     }
 
     /// Create API spec from URL or file with proper error handling and retry
     fn create_api_spec(
         api_url: Option<String>,
-        api_file: Option<PathBuf>
+        api_file: Option<PathBuf>,
+        domains: &Option<Vec<String>>,
+        interactive: bool,
     ) -> Result<Option<ApiSpec>> {
         // Process API URL if provided
         if let Some(url) = api_url {
@@ -128,13 +206,16 @@ impl FlutterProjectGenerator {
             match Self::try_load_swagger_from_url(&url) {
                 Ok(source) => {
                     // Ask for domain filter
-                    let domains = Self::prompt_for_domains()?;
+                    let domains = Self::resolve_domains(domains, interactive)?;
                     
                     return Ok(Some(ApiSpec {
                         source,
                         domains,
                     }));
                 },
+                Err(err) if !interactive => {
+                    return Err(err.context("Error loading API specification"));
+                },
                 Err(err) => {
                     // Handle error and offer retry
                     println!("{}: {}", style("Error loading API specification").red(), err);
@@ -147,11 +228,11 @@ impl FlutterProjectGenerator {
                     match retry {
                         0 => { // Try different URL
                             let new_url = Self::prompt_for_api_url(None)?;
-                            return Self::create_api_spec(Some(new_url), None);
+                            return Self::create_api_spec(Some(new_url), None, domains, interactive);
                         },
                         1 => { // Try local file
                             let file_path = Self::prompt_for_api_file(None)?;
-                            return Self::create_api_spec(None, Some(file_path));
+                            return Self::create_api_spec(None, Some(file_path), domains, interactive);
                         },
                         _ => { // Skip
                             return Ok(None);
@@ -169,13 +250,16 @@ impl FlutterProjectGenerator {
             match Self::try_load_swagger_from_file(&path) {
                 Ok(source) => {
                     // Ask for domain filter
-                    let domains = Self::prompt_for_domains()?;
+                    let domains = Self::resolve_domains(domains, interactive)?;
                     
                     return Ok(Some(ApiSpec {
                         source,
                         domains,
                     }));
                 },
+                Err(err) if !interactive => {
+                    return Err(err.context("Error loading API specification"));
+                },
                 Err(err) => {
                     // Handle error and offer retry
                     println!("{}: {}", style("Error loading API specification").red(), err);
@@ -188,11 +272,11 @@ impl FlutterProjectGenerator {
                     match retry {
                         0 => { // Try different file
                             let new_path = Self::prompt_for_api_file(None)?;
-                            return Self::create_api_spec(None, Some(new_path));
+                            return Self::create_api_spec(None, Some(new_path), domains, interactive);
                         },
                         1 => { // Try URL
                             let url = Self::prompt_for_api_url(None)?;
-                            return Self::create_api_spec(Some(url), None);
+                            return Self::create_api_spec(Some(url), None, domains, interactive);
                         },
                         _ => { // Skip
                             return Ok(None);
@@ -206,6 +290,15 @@ impl FlutterProjectGenerator {
         Ok(None)
     }
 
+    /// Use the configured domain filter, or ask for one when interactive
+    fn resolve_domains(domains: &Option<Vec<String>>, interactive: bool) -> Result<Option<Vec<String>>> {
+        match domains {
+            Some(domains) => Ok(Some(domains.clone())),
+            None if interactive => Self::prompt_for_domains(),
+            None => Ok(None),
+        }
+    }
+
     /// Prompt user for API URL with validation
     fn prompt_for_api_url(default_url: Option<&str>) -> Result<String> {
         let default = default_url.unwrap_or("https://petstore.swagger.io/v2/swagger.json");
@@ -288,8 +381,30 @@ impl FlutterProjectGenerator {
         Ok(swagger::SwaggerSource::File(path.clone()))
     }
 
+    /// Directory the project is generated in
+    pub fn project_dir(&self) -> &Path {
+        &self.config.output_dir
+    }
+
+    /// Flavors the project is generated with
+    pub fn flavors(&self) -> &[FlavorConfig] {
+        &self.config.flavors
+    }
+    
+    /// What templates know about the project being generated
+    fn project_context(&self) -> ProjectContext {
+        ProjectContext::new(
+            &self.config.name,
+            &self.config.package_name,
+            &self.config.flavors,
+            &self.config.features,
+        )
+    }
+    
     pub fn generate(&self) -> Result<()> {
         self.create_base_project()?;
+        // Written early so templates of the features below see the project's settings
+        self.write_manifest()?;
         self.setup_project_structure()?;
         self.create_flavors()?;
         self.add_features()?;
@@ -299,6 +414,27 @@ impl FlutterProjectGenerator {
         Ok(())
     }
     
+    /// Record the generator choices in the project's flutter_lazy.yaml
+    fn write_manifest(&self) -> Result<()> {
+        let mut manifest = ProjectManifest::new(
+            &self.config.name,
+            &self.config.package_name,
+            &self.config.flavors,
+            &self.config.features,
+        );
+        
+        if let Some(api_spec) = &self.api_spec {
+            manifest.set_api_source(&api_spec.source, api_spec.domains.as_deref(), self.grouping);
+            if let Some(api) = &mut manifest.api {
+                api.type_mappings = self.type_mappings.clone();
+            }
+        }
+        
+        manifest.save(&self.config.output_dir)?;
+        println!("✅ Project manifest written to {}", MANIFEST_FILE);
+        Ok(())
+    }
+    
     /// Process API features if API spec is provided
     fn process_api_features(&self) -> Result<()> {
         if let Some(api_spec) = &self.api_spec {
@@ -308,7 +444,10 @@ impl FlutterProjectGenerator {
                 &self.config.output_dir,
                 api_spec.source.clone(),
                 api_spec.domains.clone(),
-                true // data_only by default
+                true, // data_only by default
+                &swagger::TypeMappings::new(&self.type_mappings),
+                self.grouping,
+                swagger::UpdateMode::default(),
             )?;
             
             println!("✅ API features generated");
@@ -322,18 +461,25 @@ impl FlutterProjectGenerator {
         let pb = self.create_progress_bar(3);
         
         // Run flutter create command
-        let output = Command::new("flutter")
-            .args(&[
-                "create",
-                "--org", &self.config.package_name,
-                "--project-name", &self.config.name.to_case(Case::Snake),
-                self.config.output_dir.to_str().unwrap(),
-            ])
-            .output()
-            .context("Failed to execute flutter create command")?;
-        
-        if !output.status.success() {
-            return Err(anyhow::anyhow!("Flutter create command failed"));
+        let args = [
+            "create",
+            "--org", &self.config.package_name,
+            "--project-name", &self.config.name.to_case(Case::Snake),
+            self.config.output_dir.to_str().unwrap(),
+        ];
+        if vfs::is_dry_run() {
+            // Nothing flutter create writes can be planned, so only its directory is
+            println!("Would run: flutter {}", args.join(" "));
+            vfs::create_dir_all(&self.config.output_dir)?;
+        } else {
+            let output = Command::new("flutter")
+                .args(args)
+                .output()
+                .context("Failed to execute flutter create command")?;
+            
+            if !output.status.success() {
+                return Err(anyhow::anyhow!("Flutter create command failed"));
+            }
         }
         pb.inc(1);
         
@@ -378,7 +524,7 @@ impl FlutterProjectGenerator {
         let pb = self.create_progress_bar(directories.len() as u64);
         
         for dir in directories.iter() {
-            std::fs::create_dir_all(lib_dir.join(dir))
+            vfs::create_dir_all(lib_dir.join(dir))
                 .context(format!("Failed to create directory: {}", dir))?;
             pb.inc(1);
         }
@@ -386,15 +532,16 @@ impl FlutterProjectGenerator {
         // Create core files and other setup
         // Copy base core files
         let core_files = [
-            ("core/failures/failure.dart", "core/failures/failure.dart"),
-            ("core/utils/logger.dart", "core/utils/logger.dart"),
+            ("common/app_structure/core/failures/failure.dart.tmpl", "core/failures/failure.dart"),
+            ("common/app_structure/core/utils/logger.dart.tmpl", "core/utils/logger.dart"),
         ];
         
+        let context = TemplateContext::for_project(&self.project_context());
         for (src, dest) in core_files.iter() {
             self.copy_template_file(
                 src, 
                 &self.config.output_dir.join("lib").join(dest),
-                &[("{package_name}", &self.config.package_name)]
+                &context
             )?;
         }
         
@@ -408,33 +555,22 @@ impl FlutterProjectGenerator {
         let pb = self.create_progress_bar(self.config.flavors.len() as u64 + 1);
         
         // Create main.dart that uses flavors
+        let project = self.project_context();
         self.copy_template_file(
             "common/main_flavor.dart.tmpl",
             &self.config.output_dir.join("lib/main.dart"),
-            &[("{package_name}", &self.config.package_name)]
+            &TemplateContext::for_project(&project)
         )?;
         pb.inc(1);
         
-        // Create a main file for each flavor
-        for flavor in &self.config.flavors {
-            self.copy_template_file(
-                "common/main_flavor.dart.tmpl",
-                &self.config.output_dir.join(&format!("lib/main_{}.dart", flavor)),
-                &[
-                    ("{package_name}", &self.config.package_name),
-                    ("{flavor}", flavor),
-                    ("{flavor_title}", &(flavor[0..1].to_uppercase() + &flavor[1..]))
-                ]
-            )?;
+        // Create a main file and Firebase config for each flavor
+        for flavor in &project.flavors {
+            flavors::create_flavor_files(&self.config.output_dir, &project, flavor, false)?;
             pb.inc(1);
         }
         
-        // Create flavors.dart to define flavor enum
-        self.copy_template_file(
-            "common/flavors.dart",
-            &self.config.output_dir.join("lib/flavors.dart"),
-            &[("{flavors}", &self.config.flavors.join(", "))]
-        )?;
+        // Create flavors.dart to define flavor enum, and the native flavor setup
+        flavors::write_flavor_list(&self.config.output_dir, &project, false)?;
         
         pb.finish_and_clear();
         println!("✅ Flavors setup completed");
@@ -472,63 +608,12 @@ impl FlutterProjectGenerator {
         println!("Updating pubspec.yaml...");
         
         // Read the current pubspec
-        let pubspec_path = self.config.output_dir.join("pubspec.yaml");
-        let mut pubspec_content = fs::read_to_string(&pubspec_path)
-            .context("Failed to read pubspec.yaml")?;
-        
-        // Add dependencies
-        let dependencies = r#"
-  # State Management
-  flutter_bloc: ^8.1.3
-  equatable: ^2.0.5
-  
-  # Dependency Injection
-  get_it: ^7.6.4
-  injectable: ^2.3.2
-  
-  # Networking
-  dio: ^5.3.3
-  http: ^1.1.0
-  connectivity_plus: ^5.0.1
-  
-  # Local Storage
-  shared_preferences: ^2.2.2
-  
-  # Utilities
-  dartz: ^0.10.1
-  logger: ^2.0.2
-  intl: ^0.18.1
-  json_annotation: ^4.8.1
-  
-  # UI utilities
-  cached_network_image: ^3.3.0
-  flutter_svg: ^2.0.9
-  shimmer: ^3.0.0"#;
-        
-        // Add development dependencies
-        let dev_dependencies = r#"
-  # Flutter flavorizr for handling flavors
-  flutter_flavorizr: ^2.2.1
-  
-  # Code generation
-  build_runner: ^2.4.6
-  json_serializable: ^6.7.1
-  injectable_generator: ^2.4.1"#;
-        
-        // Update pubspec content
-        pubspec_content = pubspec_content.replace(
-            "dependencies:\n  flutter:", 
-            &format!("dependencies:\n  flutter:{}", dependencies)
-        );
-        
-        pubspec_content = pubspec_content.replace(
-            "dev_dependencies:\n  flutter_test:", 
-            &format!("dev_dependencies:\n  flutter_test:{}", dev_dependencies)
-        );
+        let Some(mut pubspec) = self.read_pubspec()? else {
+            return Ok(());
+        };
         
-        // Write back to pubspec.yaml
-        fs::write(&pubspec_path, pubspec_content)
-            .context("Failed to update pubspec.yaml")?;
+        dependencies::add_packages(&mut pubspec, &[PROJECT_PACKAGES])?;
+        pubspec.save()?;
         
         println!("✅ pubspec.yaml updated");
         Ok(())
@@ -545,82 +630,53 @@ impl FlutterProjectGenerator {
         ];
         
         for dir in &asset_dirs {
-            fs::create_dir_all(self.config.output_dir.join(dir))
+            vfs::create_dir_all(self.config.output_dir.join(dir))
                 .context(format!("Failed to create asset directory: {}", dir))?;
         }
         
         // Copy placeholder assets
+        let context = TemplateContext::for_project(&self.project_context());
         self.copy_template_file(
             "assets/i18n/en.json",
             &self.config.output_dir.join("assets/i18n/en.json"),
-            &[]
+            &context
         )?;
         
         // Copy placeholder images
-        self.copy_template_file(
+        copy_template_asset(
             "assets/images/placeholder.png",
-            &self.config.output_dir.join("assets/images/placeholder.png"),
-            &[]
+            &self.config.output_dir.join("assets/images/placeholder.png")
         )?;
         
         // Update pubspec to include assets
-        let pubspec_path = self.config.output_dir.join("pubspec.yaml");
-        let mut pubspec_content = fs::read_to_string(&pubspec_path)
-            .context("Failed to read pubspec.yaml")?;
-        
-        let assets_section = r#"
-  assets:
-    - assets/i18n/
-    - assets/images/
-    
-  fonts:
-    - family: AppIcons
-      fonts:
-        - asset: assets/fonts/AppIcons.ttf"#;
-        
-        // Add assets section before the end of the file
-        if !pubspec_content.contains("assets:") {
-            pubspec_content.push_str(assets_section);
-            fs::write(&pubspec_path, pubspec_content)
-                .context("Failed to update pubspec.yaml with assets")?;
+        if let Some(mut pubspec) = self.read_pubspec()? {
+            pubspec.add_asset("assets/i18n/")?;
+            pubspec.add_asset("assets/images/")?;
+            pubspec.add_font("AppIcons", &["assets/fonts/AppIcons.ttf"])?;
+            pubspec.save()?;
         }
         
         println!("✅ Asset directories created");
         Ok(())
     }
     
-    fn add_flavorizr_config(&self) -> Result<()> {
-        println!("Adding flavorizr configuration...");
-        
+    /// Read pubspec.yaml; a dry run has none to read, since flutter create did not run
+    fn read_pubspec(&self) -> Result<Option<Pubspec>> {
         let pubspec_path = self.config.output_dir.join("pubspec.yaml");
-        let mut pubspec_content = fs::read_to_string(&pubspec_path)
-            .context("Failed to read pubspec.yaml")?;
-        
-        // Generate flavorizr config
-        let mut flavor_configs = String::new();
-        for flavor in &self.config.flavors {
-            flavor_configs.push_str(&format!("\n    {}: {{}}", flavor));
+        if vfs::is_dry_run() && !vfs::exists(&pubspec_path) {
+            println!("Skipping pubspec.yaml changes: it is created by flutter create");
+            return Ok(None);
         }
         
-        let flavorizr_config = format!(r#"
-flutter_flavorizr:
-  flavors: {{{}}}"#, flavor_configs);
-        
-        // Add flavorizr config to pubspec
-        pubspec_content.push_str(&flavorizr_config);
-        fs::write(&pubspec_path, pubspec_content)
-            .context("Failed to add flavorizr config to pubspec.yaml")?;
-        
-        println!("✅ Flavorizr configuration added");
-        Ok(())
+        Pubspec::load(&self.config.output_dir).map(Some)
     }
     
     fn copy_template_dir(&self, template_subpath: &str, destination: &Path) -> Result<()> {
         crate::utils::copy_template_dir(template_subpath, destination)
     }
     
-    fn copy_template_file(&self, template_path: &str, dest_path: &Path, replacements: &[(&str, &str)]) -> Result<()> {
-        copy_template_file(template_path, dest_path, replacements)
+    fn copy_template_file(&self, template_path: &str, dest_path: &Path, context: &TemplateContext) -> Result<()> {
+        copy_template_file(template_path, dest_path, context)
     }
     
     fn create_progress_bar(&self, size: u64) -> ProgressBar {
@@ -636,43 +692,8 @@ flutter_flavorizr:
     pub fn validate(&self) -> Result<bool> {
         println!("\n{}", style("Validating generated project...").bold().cyan());
         
-        // Create default validation rules for a new project
-        let rules = ValidationSystem::default_new_project_rules();
-        
-        // Add flavor-specific rules
-        let mut flavor_rules = ValidationRules {
-            required_directories: Vec::new(),
-            required_files: self.config.flavors.iter()
-                .map(|flavor| format!("lib/main_{}.dart", flavor))
-                .collect(),
-            file_content_checks: Vec::new(),
-        };
-        
-        // Add feature-specific rules
-        for feature in &self.config.features {
-            match feature.as_str() {
-                "auth" => {
-                    flavor_rules.required_directories.push("lib/features/auth".to_string());
-                    flavor_rules.required_files.push("lib/features/auth/presentation/pages/login_page.dart".to_string());
-                },
-                "notifications" => {
-                    flavor_rules.required_directories.push("lib/features/notifications".to_string());
-                    flavor_rules.required_files.push("lib/features/notifications/domain/repositories/notification_repository.dart".to_string());
-                },
-                "main_page" => {
-                    flavor_rules.required_directories.push("lib/features/main_page".to_string());
-                    flavor_rules.required_files.push("lib/features/main_page/presentation/pages/main_page.dart".to_string());
-                },
-                _ => {}
-            }
-        }
-        
-        // Create combined rules
-        let combined_rules = ValidationRules {
-            required_directories: [rules.required_directories, flavor_rules.required_directories].concat(),
-            required_files: [rules.required_files, flavor_rules.required_files].concat(),
-            file_content_checks: rules.file_content_checks,
-        };
+        // Project rules plus the flavor and feature specific rules
+        let combined_rules = create_manifest_validation(&self.config.flavors, &self.config.features);
         
         // Create validation system with combined rules
         let validation_system = ValidationSystem::new(combined_rules);
@@ -1,17 +1,26 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use clap::{Parser, Subcommand};
 use anyhow::Result;
 use convert_case::{Case, Casing};
-use dialoguer;
 use console::style;
 
+mod config;
+mod dependencies;
+mod flavors;
 mod generator;
+mod manifest;
+mod pubspec;
+mod regions;
 mod utils;
 mod features;
 mod swagger;
+mod templates;
 mod validation;
+mod vfs;
 
-use generator::FlutterProjectGenerator;
+use config::{ConfigFile, FlavorConfig};
+use generator::{FlutterProjectGenerator, NewProjectOptions};
+use manifest::ProjectManifest;
 use features::{
     FeatureParams, 
     create_feature, 
@@ -19,12 +28,17 @@ use features::{
     create_notification_feature, 
     create_main_page_feature
 };
-use swagger::{SwaggerSource, generate_api_features};
+use swagger::{GroupingStrategy, SwaggerSource, TypeMappings, UpdateMode, diff_api_specs, generate_api_features};
 
 #[derive(Parser)]
 #[command(name = "flutter_lazy")]
 #[command(about = "Generate a Flutter project with predefined architecture", long_about = None)]
 struct Cli {
+    /// Directory of templates overriding the built-in ones file by file
+    /// (defaults to ~/.config/flutter_lazy/templates when it exists)
+    #[arg(long, global = true)]
+    templates: Option<PathBuf>,
+    
     #[command(subcommand)]
     command: Commands,
 }
@@ -53,9 +67,33 @@ enum Commands {
         #[arg(long = "api-file")]
         api_file: Option<PathBuf>,
         
+        /// Flavors to generate (comma-separated, e.g. dev,stage,prod)
+        #[arg(long, value_delimiter = ',')]
+        flavors: Option<Vec<String>>,
+        
+        /// Features to include (comma-separated: auth, notifications, main_page)
+        #[arg(long, value_delimiter = ',')]
+        features: Option<Vec<String>>,
+        
+        /// Do not generate features from a Swagger/OpenAPI spec
+        #[arg(long, default_value = "false")]
+        no_api: bool,
+        
+        /// Accept defaults for anything not given instead of prompting
+        #[arg(short, long, default_value = "false")]
+        yes: bool,
+        
+        /// Read project settings from a config file (e.g. flutter_lazy.yaml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        
         /// Skip validation of project structure
         #[arg(long, default_value = "false")]
         no_validate: bool,
+        
+        /// Print the files that would be created instead of writing them
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
     },
     
     /// Validates an existing project or feature structure
@@ -122,15 +160,19 @@ enum Commands {
         /// Skip dependency injection setup
         #[arg(long, default_value = "false")]
         no_di: bool,
+        
+        /// Print the files that would be created or changed instead of writing them
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
     },
     
     /// Creates a new feature based on a Swagger/OpenAPI specification
     FromApi {
-        /// URL to the Swagger/OpenAPI JSON specification
+        /// URL to the Swagger/OpenAPI specification (JSON or YAML)
         #[arg(short, long)]
         url: Option<String>,
         
-        /// Path to a local Swagger/OpenAPI JSON file
+        /// Path to a local Swagger/OpenAPI file (JSON or YAML)
         #[arg(short, long)]
         file: Option<PathBuf>,
         
@@ -142,6 +184,18 @@ enum Commands {
         #[arg(short, long)]
         domains: Option<String>,
         
+        /// How to group operations into features (defaults to the project's setting, or tag)
+        #[arg(long, value_enum)]
+        group_by: Option<GroupingStrategy>,
+        
+        /// Regenerate the generated files of existing features from an updated spec
+        #[arg(long, default_value = "false")]
+        update: bool,
+        
+        /// Overwrite generated files even if they were edited outside of keep regions
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
         /// Skip generating cubits/state management (data layer only)
         #[arg(long, default_value = "true")]
         data_only: bool,
@@ -149,18 +203,173 @@ enum Commands {
         /// Skip validation of generated API features
         #[arg(long, default_value = "false")]
         no_validate: bool,
+        
+        /// Print the files that would be created or changed instead of writing them
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+    },
+    
+    /// Adds or removes flavors of an existing project
+    Flavor {
+        #[command(subcommand)]
+        command: FlavorCommands,
+    },
+    
+    /// Reports the changes between two versions of a Swagger/OpenAPI spec
+    ApiDiff {
+        /// Previous spec (URL or file)
+        #[arg(long)]
+        old: String,
+        
+        /// New spec (URL or file)
+        #[arg(long)]
+        new: String,
+        
+        /// Print the report as JSON
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum FlavorCommands {
+    /// Adds a flavor: its entry point, Firebase config and Flavor enum value
+    Add {
+        /// Flavor name (e.g. qa)
+        name: String,
+        
+        /// App name shown on the device (defaults to the project title and flavor name)
+        #[arg(long)]
+        display_name: Option<String>,
+        
+        /// API base URL (defaults to https://<flavor>-api.example.com)
+        #[arg(long)]
+        base_url: Option<String>,
+        
+        /// Application/bundle id suffix (defaults to .<flavor>)
+        #[arg(long)]
+        suffix: Option<String>,
+        
+        /// Project directory
+        #[arg(short, long)]
+        project: Option<PathBuf>,
+        
+        /// Overwrite flavor files even if they were edited outside of keep regions
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
+        /// Print the files that would be created or changed instead of writing them
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+    },
+    
+    /// Removes a flavor and the files generated for it
+    Remove {
+        /// Flavor name
+        name: String,
+        
+        /// Project directory
+        #[arg(short, long)]
+        project: Option<PathBuf>,
+        
+        /// Remove flavor files even if they were edited by hand
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
+        /// Print the files that would be removed or changed instead of writing them
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
     },
 }
 
+/// Treat http(s) locations as URLs and anything else as a file path
+fn spec_source(location: &str) -> SwaggerSource {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        SwaggerSource::Url(location.to_string())
+    } else {
+        SwaggerSource::File(PathBuf::from(location))
+    }
+}
+
+/// Use the given project directory, the nearest one with a flutter_lazy.yaml,
+/// or ask for one
+fn resolve_project_dir(project: &Option<PathBuf>, prompt: &str) -> Result<PathBuf> {
+    if let Some(path) = project {
+        return Ok(path.clone());
+    }
+    
+    if let Some(found) = manifest::find_project_dir(Path::new(".")) {
+        println!("Using project at {} (found {})", style(found.display()).bold(), manifest::MANIFEST_FILE);
+        return Ok(found);
+    }
+    
+    let dir_str = dialoguer::Input::<String>::new()
+        .with_prompt(prompt)
+        .default(".".into())
+        .interact()?;
+    
+    Ok(PathBuf::from(dir_str))
+}
+
+/// Run a generation step as a dry run, or as a transaction that writes all of
+/// its files or none of them; returns whether files were written
+fn run_generation(dry_run: bool, root: &Path, generate: impl FnOnce() -> Result<()>) -> Result<bool> {
+    if dry_run {
+        vfs::dry_run(root, generate)?;
+        return Ok(false);
+    }
+    
+    vfs::transaction(generate)?;
+    Ok(true)
+}
+
+/// Load the project manifest, warning if it was written by another version
+fn load_manifest(project_dir: &Path) -> Result<Option<ProjectManifest>> {
+    let project_manifest = ProjectManifest::load(project_dir)?;
+    if let Some(m) = &project_manifest {
+        m.check_template_version();
+    }
+    Ok(project_manifest)
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if let Some(dir) = &cli.templates {
+        templates::set_template_dir(dir)?;
+    }
 
     match &cli.command {
-        Commands::New { name, output, package_name, api_url, api_file, no_validate } => {
+        Commands::New { 
+            name, 
+            output, 
+            package_name, 
+            api_url, 
+            api_file, 
+            flavors, 
+            features, 
+            no_api, 
+            yes, 
+            config, 
+            no_validate,
+            dry_run,
+        } => {
+            // Values from the command line win over the config file
+            let file_config = match config {
+                Some(path) => ConfigFile::load(path)?,
+                None => ConfigFile::default(),
+            };
+            if let Some(version) = &file_config.template_version {
+                if version != manifest::TEMPLATE_VERSION {
+                    println!("{} Config was written by flutter_lazy {}; generating with {}", 
+                        style("⚠️").yellow().bold(), version, manifest::TEMPLATE_VERSION);
+                }
+            }
+            let interactive = config::is_interactive(*yes);
+            
             // Interactive mode if name is not provided
-            let project_name = match name {
-                Some(n) => n.clone(),
-                None => {
+            let project_name = match name.clone().or(file_config.name) {
+                Some(n) => n,
+                None if interactive => {
                     // Prompt for project name
                     dialoguer::Input::<String>::new()
                         .with_prompt("Enter project name")
@@ -174,29 +383,34 @@ fn main() -> Result<()> {
                             }
                         })
                         .interact()?
+                },
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "Project name is required when not running interactively (use --name or set `name` in the config file)"
+                    ));
                 }
             };
             
             // Ask for output directory if not provided
-            let output_dir = match output {
-                Some(path) => path.clone(),
-                None => {
+            let output_dir = match output.clone().or(file_config.output) {
+                Some(path) => path,
+                None if interactive => {
                     let dir_str = dialoguer::Input::<String>::new()
                         .with_prompt("Enter output directory (press Enter for current directory)")
                         .default(".".into())
                         .interact()?;
                     
                     PathBuf::from(dir_str)
-                }
+                },
+                None => PathBuf::from("."),
             };
             
             // Ask for package name if not provided
-            let package = match package_name {
-                Some(pkg) => pkg.clone(),
-                None => {
-                    let snake_case_name = project_name.to_case(Case::Snake);
-                    let default_package = format!("com.example.{}", snake_case_name);
-                    
+            let snake_case_name = project_name.to_case(Case::Snake);
+            let default_package = format!("com.example.{}", snake_case_name);
+            let package = match package_name.clone().or(file_config.package_name) {
+                Some(pkg) => pkg,
+                None if interactive => {
                     dialoguer::Input::<String>::new()
                         .with_prompt("Enter package name (e.g., com.example.myapp)")
                         .default(default_package)
@@ -208,17 +422,59 @@ fn main() -> Result<()> {
                             }
                         })
                         .interact()?
-                }
+                },
+                None => default_package,
+            };
+            
+            // API settings: flags override the config file, and `enabled: false` acts like --no-api
+            let api_config = file_config.api.unwrap_or_default();
+            let (api_url, api_file) = if api_url.is_some() || api_file.is_some() {
+                (api_url.clone(), api_file.clone())
+            } else {
+                (api_config.url, api_config.file)
+            };
+            let skip_api = *no_api || (config.is_some() && !api_config.enabled);
+            
+            let options = NewProjectOptions {
+                name: project_name.clone(),
+                output: Some(output_dir),
+                package_name: Some(package),
+                flavors: match flavors {
+                    Some(names) => Some(config::flavors_from_names(names, file_config.flavors.as_deref())),
+                    None => file_config.flavors,
+                },
+                features: features.clone().or(file_config.features),
+                api_url,
+                api_file,
+                api_domains: api_config.domains,
+                api_type_mappings: api_config.type_mappings,
+                api_grouping: api_config.grouping,
+                no_api: skip_api,
+                interactive,
             };
             
             println!("\n{}", style("Generating Flutter project...").bold().green());
-            let generator = FlutterProjectGenerator::new(&project_name, &Some(output_dir), &Some(package), api_url, api_file)?;
-            generator.generate()?;
+            let generator = FlutterProjectGenerator::new(options)?;
+            let project_dir = generator.project_dir();
+            let created = !project_dir.exists();
+            match run_generation(*dry_run, project_dir, || generator.generate()) {
+                Ok(true) => {},
+                Ok(false) => return Ok(()),
+                Err(err) => {
+                    // flutter create writes outside the transaction, so remove its project too
+                    if created && project_dir.exists() {
+                        std::fs::remove_dir_all(project_dir)?;
+                    }
+                    return Err(err);
+                },
+            }
             
             println!("\n✅ Project {} has been generated successfully!", style(&project_name).bold());
             println!("To start the project:");
             println!("  cd {}", project_name);
-            println!("  flutter run --flavor dev -t lib/main_dev.dart");
+            if let Some(flavor) = generator.flavors().first() {
+                println!("  flutter run --flavor {0} -t lib/main_{0}.dart", flavor.name);
+            }
             
             // Run validation if not explicitly disabled
             if !no_validate {
@@ -247,7 +503,8 @@ fn main() -> Result<()> {
             no_utils,
             no_routing,
             no_di,
-            no_validate 
+            no_validate,
+            dry_run,
         } => {
             println!("{}", style("Feature Generator").bold().cyan());
             
@@ -307,17 +564,8 @@ fn main() -> Result<()> {
             };
             
             // Determine project directory
-            let project_dir = match project {
-                Some(path) => path.clone(),
-                None => {
-                    let dir_str = dialoguer::Input::<String>::new()
-                        .with_prompt("Project directory (press Enter for current directory)")
-                        .default(".".into())
-                        .interact()?;
-                    
-                    PathBuf::from(dir_str)
-                }
-            };
+            let project_dir = resolve_project_dir(project, "Project directory (press Enter for current directory)")?;
+            let mut project_manifest = load_manifest(&project_dir)?;
             
             // Default to full architecture (not minimal)
             let use_minimal = *minimal;
@@ -394,24 +642,35 @@ fn main() -> Result<()> {
             
             // Create the feature
             println!("\n{}", style("Generating feature...").bold().green());
-            
-            // Use predefined feature generators for specialized features only
-            // These have special implementations beyond just naming conventions
-            match feature_name.as_str() {
-                "authentication" | "auth" => {
-                    create_auth_feature(&project_dir)?;
-                },
-                "notifications" | "notification" => {
-                    create_notification_feature(&project_dir)?;
-                },
-                "main_page" | "home" => {
-                    create_main_page_feature(&project_dir)?;
-                },
-                _ => {
-                    // For all other features (including settings, profile, etc.), use the regular feature creation
-                    // This allows adding any new feature dynamically without needing a helper function
-                    create_feature(&project_dir, params)?;
+            let written = run_generation(*dry_run, &project_dir, || {
+                // Use predefined feature generators for specialized features only
+                // These have special implementations beyond just naming conventions
+                match feature_name.as_str() {
+                    "authentication" | "auth" => {
+                        create_auth_feature(&project_dir)?;
+                    },
+                    "notifications" | "notification" => {
+                        create_notification_feature(&project_dir)?;
+                    },
+                    "main_page" | "home" => {
+                        create_main_page_feature(&project_dir)?;
+                    },
+                    _ => {
+                        // For all other features (including settings, profile, etc.), use the regular feature creation
+                        // This allows adding any new feature dynamically without needing a helper function
+                        create_feature(&project_dir, params)?;
+                    }
+                }
+                
+                // Record the feature so later commands know about it
+                if let Some(m) = &mut project_manifest {
+                    m.add_feature(&feature_name.to_case(Case::Snake));
+                    m.save(&project_dir)?;
                 }
+                Ok(())
+            })?;
+            if !written {
+                return Ok(());
             }
             
             println!("\n✅ Feature '{}' created successfully!", style(&feature_name).bold());
@@ -432,19 +691,31 @@ fn main() -> Result<()> {
             }
         },
         
-        Commands::FromApi { url, file, project, domains, data_only, no_validate } => {
+        Commands::FromApi { url, file, project, domains, group_by, update, force, data_only, no_validate, dry_run } => {
             println!("{}", style("API Feature Generator").bold().cyan());
             
-            // Get the Swagger URL or file path
-            let source = match (url, file) {
-                (Some(u), _) => {
+            // Get the project directory
+            let project_dir = resolve_project_dir(project, "Enter project directory (press Enter for current directory)")?;
+            let mut project_manifest = load_manifest(&project_dir)?;
+            
+            // Get the Swagger URL or file path, falling back to the one the project was generated from
+            let recorded_source = project_manifest.as_ref().and_then(|m| m.api_source());
+            let source = match (url, file, recorded_source) {
+                (Some(u), _, _) => {
                     println!("Using Swagger API from URL: {}", style(u).bold());
                     SwaggerSource::Url(u.clone())
                 },
-                (_, Some(f)) => {
+                (_, Some(f), _) => {
                     println!("Using Swagger API from file: {}", style(f.display()).bold());
                     SwaggerSource::File(f.clone())
                 },
+                (_, _, Some(recorded)) => {
+                    match &recorded {
+                        SwaggerSource::Url(u) => println!("Using Swagger API from {}: {}", manifest::MANIFEST_FILE, style(u).bold()),
+                        SwaggerSource::File(f) => println!("Using Swagger API from {}: {}", manifest::MANIFEST_FILE, style(f.display()).bold()),
+                    }
+                    recorded
+                },
                 _ => {
                     // Interactive mode - prompt for URL
                     let input_url = dialoguer::Input::<String>::new()
@@ -456,19 +727,6 @@ fn main() -> Result<()> {
                 }
             };
             
-            // Get the project directory
-            let project_dir = match project {
-                Some(path) => path.clone(),
-                None => {
-                    let dir_str = dialoguer::Input::<String>::new()
-                        .with_prompt("Enter project directory (press Enter for current directory)")
-                        .default(".".into())
-                        .interact()?;
-                    
-                    PathBuf::from(dir_str)
-                }
-            };
-            
             // Filter domains if specified
             let domain_list = domains.as_ref().map(|d| {
                 d.split(',')
@@ -479,7 +737,30 @@ fn main() -> Result<()> {
             println!("\n{}", style("Generating API-based features...").bold().green());
             
             // Call the API feature generator
-            generate_api_features(&project_dir, source.clone(), domain_list.clone(), *data_only)?;
+            let type_mappings = project_manifest.as_ref()
+                .map(|m| m.type_mappings())
+                .unwrap_or_default();
+            // Updates regenerate the domains recorded for the project unless others are given
+            let domain_list = match (domain_list, project_manifest.as_ref().and_then(|m| m.api.as_ref())) {
+                (None, Some(api)) if *update => api.domains.clone(),
+                (domain_list, _) => domain_list,
+            };
+            let grouping = group_by
+                .or_else(|| project_manifest.as_ref().map(|m| m.grouping()))
+                .unwrap_or_default();
+            let written = run_generation(*dry_run, &project_dir, || {
+                generate_api_features(&project_dir, source.clone(), domain_list.clone(), *data_only, &type_mappings, grouping, UpdateMode { update: *update, force: *force })?;
+                
+                // Record the API source, grouping and generated domains in the manifest
+                if let Some(m) = &mut project_manifest {
+                    m.set_api_source(&source, domain_list.as_deref(), grouping);
+                    m.save(&project_dir)?;
+                }
+                Ok(())
+            })?;
+            if !written {
+                return Ok(());
+            }
             
             println!("\n✅ API-based features have been generated successfully!");
             
@@ -534,21 +815,53 @@ fn main() -> Result<()> {
             }
         },
         
+        Commands::Flavor { command } => match command {
+            FlavorCommands::Add { name, display_name, base_url, suffix, project, force, dry_run } => {
+                let project_dir = resolve_project_dir(project, "Enter project directory (press Enter for current directory)")?;
+                let flavor = FlavorConfig {
+                    name: name.clone(),
+                    display_name: display_name.clone(),
+                    base_url: base_url.clone(),
+                    suffix: suffix.clone(),
+                };
+                
+                if !run_generation(*dry_run, &project_dir, || flavors::add_flavor(&project_dir, flavor, *force))? {
+                    return Ok(());
+                }
+                
+                println!("\n✅ Flavor '{}' added successfully!", style(name).bold());
+                println!("To run it:");
+                println!("  flutter run --flavor {0} -t lib/main_{0}.dart", name);
+            },
+            
+            FlavorCommands::Remove { name, project, force, dry_run } => {
+                let project_dir = resolve_project_dir(project, "Enter project directory (press Enter for current directory)")?;
+                
+                if !run_generation(*dry_run, &project_dir, || flavors::remove_flavor(&project_dir, name, *force))? {
+                    return Ok(());
+                }
+                
+                println!("\n✅ Flavor '{}' removed successfully!", style(name).bold());
+            },
+        },
+        
+        Commands::ApiDiff { old, new, json } => {
+            // Compare with the Dart types of the current project, if run inside one
+            let type_mappings = manifest::find_project_dir(Path::new("."))
+                .map(|dir| ProjectManifest::load(&dir))
+                .transpose()?
+                .flatten()
+                .map(|m| m.type_mappings())
+                .unwrap_or_else(TypeMappings::default);
+            
+            diff_api_specs(spec_source(old), spec_source(new), &type_mappings, *json)?;
+        },
+        
         Commands::Validate { project, feature, api_feature } => {
             println!("{}", style("Flutter Lazy Validation").bold().cyan());
             
             // Get project directory
-            let project_dir = match project {
-                Some(path) => path.clone(),
-                None => {
-                    let dir_str = dialoguer::Input::<String>::new()
-                        .with_prompt("Enter project directory to validate (press Enter for current directory)")
-                        .default(".".into())
-                        .interact()?;
-                    
-                    PathBuf::from(dir_str)
-                }
-            };
+            let project_dir = resolve_project_dir(project, "Enter project directory to validate (press Enter for current directory)")?;
             
             // Check if project directory exists and is a Flutter project
             if !project_dir.exists() || !project_dir.is_dir() {
@@ -567,14 +880,21 @@ fn main() -> Result<()> {
                 // Validate a specific feature
                 println!("\n{}", style(format!("Validating feature: {}", feat_name)).bold().green());
                 
-                let validation_rules = validation::ValidationSystem::default_feature_rules(&feat_name);
+                let validation_rules = validation::ValidationSystem::default_feature_rules(feat_name);
                 let validation_system = validation::ValidationSystem::new(validation_rules);
                 validation_system.run_validation(&project_dir);
             } else if let Some(api_feat_name) = api_feature {
                 // Validate a specific API feature
                 println!("\n{}", style(format!("Validating API feature: {}", api_feat_name)).bold().green());
                 
-                let validation_rules = validation::create_api_feature_validation(&api_feat_name);
+                let validation_rules = validation::create_api_feature_validation(api_feat_name);
+                let validation_system = validation::ValidationSystem::new(validation_rules);
+                validation_system.run_validation(&project_dir);
+            } else if let Some(project_manifest) = load_manifest(&project_dir)? {
+                // Validate against the flavors and features the project was generated with
+                println!("\n{}", style(format!("Validating project structure against {}...", manifest::MANIFEST_FILE)).bold().green());
+                
+                let validation_rules = validation::create_manifest_validation(&project_manifest.flavors, &project_manifest.features);
                 let validation_system = validation::ValidationSystem::new(validation_rules);
                 validation_system.run_validation(&project_dir);
             } else {
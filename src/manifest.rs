@@ -0,0 +1,234 @@
+// manifest.rs
+// Project manifest recording the choices a project was generated with
+
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Context, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, ApiConfig, FlavorConfig};
+use crate::swagger::{GroupingStrategy, SwaggerSource, TypeMappings};
+use crate::vfs;
+
+/// File name of the manifest written to the root of generated projects
+pub const MANIFEST_FILE: &str = "flutter_lazy.yaml";
+
+/// Version of the generator (and its bundled templates) writing the manifest
+pub const TEMPLATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Generator choices recorded in `flutter_lazy.yaml`
+///
+/// The keys match the `--config` file format, so a manifest can be used to
+/// recreate a project with the same settings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectManifest {
+    pub template_version: String,
+    pub name: String,
+    pub package_name: String,
+    #[serde(default)]
+    pub flavors: Vec<FlavorConfig>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<ApiConfig>,
+}
+
+impl ProjectManifest {
+    pub fn new(name: &str, package_name: &str, flavors: &[FlavorConfig], features: &[String]) -> Self {
+        Self {
+            template_version: TEMPLATE_VERSION.to_string(),
+            name: name.to_string(),
+            package_name: package_name.to_string(),
+            flavors: flavors.to_vec(),
+            features: features.to_vec(),
+            api: None,
+        }
+    }
+
+    /// Load the manifest from a project directory, if the project has one
+    ///
+    /// A relative API spec path is resolved against the project directory.
+    pub fn load(project_dir: &Path) -> Result<Option<Self>> {
+        let manifest_path = project_dir.join(MANIFEST_FILE);
+        if !vfs::exists(&manifest_path) {
+            return Ok(None);
+        }
+
+        let content = vfs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read manifest: {}", manifest_path.display()))?;
+
+        let mut manifest: ProjectManifest = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse manifest: {}", manifest_path.display()))?;
+
+        if let Some(file) = manifest.api.as_mut().and_then(|api| api.file.as_mut()) {
+            *file = config::resolve_relative(project_dir, file);
+        }
+
+        Ok(Some(manifest))
+    }
+
+    /// Write the manifest to the root of a project directory
+    ///
+    /// The API spec path is stored relative to the project, so the manifest
+    /// works in any checkout.
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let manifest_path = project_dir.join(MANIFEST_FILE);
+        let mut manifest = self.clone();
+        if let Some(file) = manifest.api.as_mut().and_then(|api| api.file.as_mut()) {
+            *file = relative_to_project(project_dir, file);
+        }
+        let yaml = serde_yaml::to_string(&manifest)
+            .context("Failed to serialize project manifest")?;
+
+        let content = format!("# Generated by flutter_lazy. Records the settings this project was created with.\n{}", yaml);
+        vfs::write(&manifest_path, content)
+            .with_context(|| format!("Failed to write manifest: {}", manifest_path.display()))?;
+
+        Ok(())
+    }
+
+    /// Record a feature added after project creation
+    pub fn add_feature(&mut self, feature_name: &str) {
+        if !self.features.iter().any(|f| f == feature_name) {
+            self.features.push(feature_name.to_string());
+        }
+    }
+
+    /// Record the API spec features were generated from, merging domain filters
+    pub fn set_api_source(&mut self, source: &SwaggerSource, domains: Option<&[String]>, grouping: GroupingStrategy) {
+        let mut api = ApiConfig {
+            type_mappings: self.api.as_ref().map(|a| a.type_mappings.clone()).unwrap_or_default(),
+            grouping,
+            ..ApiConfig::default()
+        };
+        match source {
+            SwaggerSource::Url(url) => api.url = Some(url.clone()),
+            SwaggerSource::File(path) => {
+                api.file = Some(fs::canonicalize(path).unwrap_or_else(|_| path.clone()));
+            }
+        }
+
+        // Keep every domain generated so far; no filter means all domains
+        api.domains = match (self.api.as_ref(), domains) {
+            (None, new) => new.map(|d| d.to_vec()),
+            (Some(previous), Some(new)) => previous.domains.clone().map(|mut existing| {
+                for domain in new {
+                    if !existing.contains(domain) {
+                        existing.push(domain.clone());
+                    }
+                }
+                existing
+            }),
+            (Some(_), None) => None,
+        };
+
+        self.api = Some(api);
+    }
+
+    /// Type mappings for API generation, with the project's overrides applied
+    pub fn type_mappings(&self) -> TypeMappings {
+        self.api.as_ref()
+            .map(|api| TypeMappings::new(&api.type_mappings))
+            .unwrap_or_default()
+    }
+
+    /// How the project's API operations are grouped into features
+    pub fn grouping(&self) -> GroupingStrategy {
+        self.api.as_ref().map(|api| api.grouping).unwrap_or_default()
+    }
+
+    /// The API source recorded at generation time
+    pub fn api_source(&self) -> Option<SwaggerSource> {
+        let api = self.api.as_ref()?;
+        if !api.enabled {
+            return None;
+        }
+
+        match (&api.url, &api.file) {
+            (Some(url), _) => Some(SwaggerSource::Url(url.clone())),
+            (_, Some(file)) => Some(SwaggerSource::File(file.clone())),
+            _ => None,
+        }
+    }
+
+    /// Warn when the project was generated by a different generator version
+    pub fn check_template_version(&self) {
+        if self.template_version != TEMPLATE_VERSION {
+            println!(
+                "{} This project was generated with flutter_lazy {} but you are running {}. Generated files may not match the existing layout.",
+                style("⚠️").yellow().bold(),
+                self.template_version,
+                TEMPLATE_VERSION
+            );
+        }
+    }
+}
+
+/// A path relative to the project directory, when both can be resolved
+fn relative_to_project(project_dir: &Path, path: &Path) -> PathBuf {
+    let project_dir = fs::canonicalize(project_dir).unwrap_or_else(|_| project_dir.to_path_buf());
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if !path.is_absolute() {
+        return path;
+    }
+    pathdiff::diff_paths(&path, &project_dir).unwrap_or(path)
+}
+
+/// Find the nearest directory at or above `start` containing a manifest
+pub fn find_project_dir(start: &Path) -> Option<PathBuf> {
+    let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+    start
+        .ancestors()
+        .find(|dir| dir.join(MANIFEST_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(dir.join("lib/features")).unwrap();
+
+        let mut manifest = ProjectManifest::new("shop", "com.acme.shop", &[FlavorConfig::named("dev")], &["auth".into()]);
+        manifest.add_feature("auth");
+        manifest.add_feature("profile");
+        manifest.set_api_source(&SwaggerSource::Url("https://example.com/openapi.json".into()), Some(&["pets".into()]), GroupingStrategy::Tag);
+        manifest.save(&dir).unwrap();
+
+        let loaded = ProjectManifest::load(&dir).unwrap().unwrap();
+        assert!(crate::config::ConfigFile::load(&dir.join(MANIFEST_FILE)).is_ok());
+        assert_eq!(loaded.features, vec!["auth", "profile"]);
+        assert_eq!(loaded.template_version, TEMPLATE_VERSION);
+        assert!(matches!(loaded.api_source(), Some(SwaggerSource::Url(_))));
+
+        // The project root is found from any directory below it
+        let found = find_project_dir(&dir.join("lib/features")).unwrap();
+        assert_eq!(found, fs::canonicalize(&dir).unwrap());
+    }
+
+    #[test]
+    fn test_api_file_saved_relative() {
+        let temp = tempfile::tempdir().unwrap();
+        let project_dir = temp.path().join("shop");
+        fs::create_dir_all(temp.path().join("specs")).unwrap();
+        fs::create_dir_all(&project_dir).unwrap();
+        let spec = temp.path().join("specs/openapi.yaml");
+        fs::write(&spec, "openapi: 3.0.0\n").unwrap();
+
+        let mut manifest = ProjectManifest::new("shop", "com.acme.shop", &[FlavorConfig::named("dev")], &[]);
+        manifest.set_api_source(&SwaggerSource::File(spec.clone()), None, GroupingStrategy::Tag);
+        manifest.save(&project_dir).unwrap();
+
+        let saved = fs::read_to_string(project_dir.join(MANIFEST_FILE)).unwrap();
+        assert!(saved.contains("file: ../specs/openapi.yaml"), "{}", saved);
+
+        let loaded = ProjectManifest::load(&project_dir).unwrap().unwrap();
+        let Some(SwaggerSource::File(file)) = loaded.api_source() else { panic!("no file source") };
+        assert_eq!(fs::canonicalize(file).unwrap(), fs::canonicalize(&spec).unwrap());
+    }
+}
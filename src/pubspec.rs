@@ -0,0 +1,323 @@
+// pubspec.rs
+// Editing pubspec.yaml in place, keeping its comments and layout
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+
+use crate::vfs;
+
+/// A pubspec.yaml being edited
+///
+/// Edits only insert lines, so comments and formatting of the rest of the file
+/// stay as they are. Keys and list items that already exist are left alone.
+#[derive(Debug, Clone)]
+pub struct Pubspec {
+    path: PathBuf,
+    lines: Vec<String>,
+}
+
+/// Indentation of a line, in spaces
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Whether a line holds no YAML content
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// The value after `key:` on a line, without its comment, if the line holds that key
+fn key_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let rest = line.trim_start().strip_prefix(key)?.strip_prefix(':')?;
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some(rest.split(" #").next().unwrap_or_default().trim())
+}
+
+impl Pubspec {
+    /// Load the pubspec.yaml of a project
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join("pubspec.yaml");
+        let content = vfs::read_to_string(&path).context("Failed to read pubspec.yaml")?;
+        Self::parse(path, &content)
+    }
+
+    fn parse(path: PathBuf, content: &str) -> Result<Self> {
+        serde_yaml::from_str::<serde_yaml::Mapping>(content).context("Failed to parse pubspec.yaml")?;
+        Ok(Self {
+            path,
+            lines: content.lines().map(str::to_string).collect(),
+        })
+    }
+
+    /// Write the edited pubspec.yaml back
+    pub fn save(&self) -> Result<()> {
+        let content = self.to_string();
+        serde_yaml::from_str::<serde_yaml::Mapping>(&content)
+            .context("Editing pubspec.yaml produced invalid YAML")?;
+        vfs::write(&self.path, content).context("Failed to write pubspec.yaml")
+    }
+
+    /// The package name
+    pub fn name(&self) -> Option<String> {
+        let line = self.find_key(0..self.lines.len(), 0, "name")?;
+        let name = key_value(&self.lines[line], "name")?.trim_matches(|c| c == '"' || c == '\'');
+        Some(name.to_string()).filter(|name| !name.is_empty())
+    }
+
+    /// Add a dependency unless the pubspec already has it; returns whether it was added
+    pub fn add_dependency(&mut self, name: &str, version: &str) -> Result<bool> {
+        let section = self.section(None, "dependencies")?;
+        self.add_key(section, name, version)
+    }
+
+    /// Add a dev dependency unless the pubspec already has it; returns whether it was added
+    pub fn add_dev_dependency(&mut self, name: &str, version: &str) -> Result<bool> {
+        let section = self.section(None, "dev_dependencies")?;
+        self.add_key(section, name, version)
+    }
+
+    /// Add an asset path to the `flutter` section unless it is listed already
+    pub fn add_asset(&mut self, asset: &str) -> Result<bool> {
+        let flutter = self.section(None, "flutter")?;
+        let assets = self.section(Some(flutter), "assets")?;
+        if self.items(assets).any(|item| item.trim_matches(|c| c == '"' || c == '\'') == asset) {
+            return Ok(false);
+        }
+        self.add_item(assets, &[format!("- {}", asset)]);
+        Ok(true)
+    }
+
+    /// Add a font family to the `flutter` section unless a family of that name exists
+    pub fn add_font(&mut self, family: &str, assets: &[&str]) -> Result<bool> {
+        let flutter = self.section(None, "flutter")?;
+        let fonts = self.section(Some(flutter), "fonts")?;
+        if self.items(fonts).any(|item| key_value(item, "family") == Some(family)) {
+            return Ok(false);
+        }
+
+        let mut item = vec![format!("- family: {}", family), "  fonts:".to_string()];
+        item.extend(assets.iter().map(|asset| format!("    - asset: {}", asset)));
+        self.add_item(fonts, &item);
+        Ok(true)
+    }
+
+    /// Line of `key` at exactly `indent` within a range of lines
+    fn find_key(&self, lines: Range<usize>, indent: usize, key: &str) -> Option<usize> {
+        lines
+            .into_iter()
+            .find(|&index| {
+                let line = &self.lines[index];
+                !is_blank_or_comment(line) && self::indent(line) == indent && key_value(line, key).is_some()
+            })
+    }
+
+    /// End of the block a key line opens: the first content or comment line that
+    /// is not indented deeper, except list items at the key's own indentation
+    fn block_end(&self, header: usize) -> usize {
+        let header_indent = indent(&self.lines[header]);
+        (header + 1..self.lines.len())
+            .find(|&index| {
+                let line = &self.lines[index];
+                !line.trim().is_empty()
+                    && indent(line) <= header_indent
+                    && !(indent(line) == header_indent && line.trim_start().starts_with('-'))
+            })
+            .unwrap_or(self.lines.len())
+    }
+
+    /// Indentation of the entries of a block, from its first entry or two deeper than the key
+    fn child_indent(&self, header: usize) -> usize {
+        (header + 1..self.block_end(header))
+            .map(|index| &self.lines[index])
+            .find(|line| !is_blank_or_comment(line))
+            .map_or(indent(&self.lines[header]) + 2, |line| indent(line))
+    }
+
+    /// Line of a mapping key, added empty at the end of its parent if missing
+    ///
+    /// `parent` is the line of the enclosing key, or `None` for top-level keys.
+    fn section(&mut self, parent: Option<usize>, key: &str) -> Result<usize> {
+        let (lines, indent) = match parent {
+            Some(parent) => (parent + 1..self.block_end(parent), self.child_indent(parent)),
+            None => (0..self.lines.len(), 0),
+        };
+
+        if let Some(line) = self.find_key(lines, indent, key) {
+            match key_value(&self.lines[line], key) {
+                Some("") => {},
+                // An empty flow collection or null is replaced by a block one
+                Some("{}" | "[]" | "~" | "null") => self.lines[line] = format!("{}{}:", " ".repeat(indent), key),
+                _ => return Err(anyhow::anyhow!("Cannot edit `{}` in pubspec.yaml: it is written inline", key)),
+            }
+            return Ok(line);
+        }
+
+        let at = match parent {
+            Some(parent) => self.content_end(parent),
+            None => {
+                // Keep top-level sections apart with a blank line
+                if self.lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+                self.lines.len()
+            },
+        };
+        self.lines.insert(at, format!("{}{}:", " ".repeat(indent), key));
+        Ok(at)
+    }
+
+    /// Position after the last entry of a block, before comments and blank lines that follow it
+    fn content_end(&self, header: usize) -> usize {
+        (header + 1..self.block_end(header))
+            .rev()
+            .find(|&index| !is_blank_or_comment(&self.lines[index]))
+            .map_or(header + 1, |index| index + 1)
+    }
+
+    /// Add `key: value` to a mapping block unless the key is there
+    fn add_key(&mut self, section: usize, key: &str, value: &str) -> Result<bool> {
+        let indent = self.child_indent(section);
+        if self.find_key(section + 1..self.block_end(section), indent, key).is_some() {
+            return Ok(false);
+        }
+
+        let at = self.content_end(section);
+        self.lines.insert(at, format!("{}{}: {}", " ".repeat(indent), key, value));
+        Ok(true)
+    }
+
+    /// Values of the items of a list block, without their `- `
+    fn items(&self, section: usize) -> impl Iterator<Item = &str> {
+        let indent = self.child_indent(section);
+        self.lines[section + 1..self.block_end(section)]
+            .iter()
+            .filter(move |line| self::indent(line) == indent)
+            .filter_map(|line| line.trim_start().strip_prefix('-'))
+            .map(|item| item.split(" #").next().unwrap_or_default().trim())
+    }
+
+    /// Add an item, given as lines relative to the list's indentation, at the end of a list block
+    fn add_item(&mut self, section: usize, item: &[String]) {
+        let indent = " ".repeat(self.child_indent(section));
+        let at = self.content_end(section);
+        for (offset, line) in item.iter().enumerate() {
+            self.lines.insert(at + offset, format!("{}{}", indent, line));
+        }
+    }
+}
+
+impl std::fmt::Display for Pubspec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parts of a `flutter create` pubspec the generator edits
+    const PUBSPEC: &str = "name: shop
+description: \"A new Flutter project.\"
+
+dependencies:
+  flutter:
+    sdk: flutter
+
+  # The following adds the Cupertino Icons font to your application.
+  cupertino_icons: ^1.0.8
+
+dev_dependencies:
+  flutter_test:
+    sdk: flutter
+
+# The following section is specific to Flutter packages.
+flutter:
+
+  uses-material-design: true
+
+  # To add assets to your application, add an assets section, like this:
+  # assets:
+  #   - images/a_dot_burr.jpeg
+";
+
+    fn pubspec(content: &str) -> Pubspec {
+        Pubspec::parse(PathBuf::from("pubspec.yaml"), content).unwrap()
+    }
+
+    #[test]
+    fn test_add_dependencies_and_assets() {
+        let mut pubspec = pubspec(PUBSPEC);
+        assert_eq!(pubspec.name().as_deref(), Some("shop"));
+        assert!(pubspec.add_dependency("dio", "^5.3.3").unwrap());
+        assert!(!pubspec.add_dependency("cupertino_icons", "^2.0.0").unwrap());
+        assert!(!pubspec.add_dependency("flutter", "^1.0.0").unwrap());
+        assert!(pubspec.add_dev_dependency("build_runner", "^2.4.6").unwrap());
+        assert!(pubspec.add_asset("assets/images/").unwrap());
+        assert!(pubspec.add_asset("assets/i18n/").unwrap());
+        assert!(!pubspec.add_asset("assets/images/").unwrap());
+        assert!(pubspec.add_font("AppIcons", &["assets/fonts/AppIcons.ttf"]).unwrap());
+        assert!(!pubspec.add_font("AppIcons", &["assets/fonts/Other.ttf"]).unwrap());
+
+        assert_eq!(pubspec.to_string(), "name: shop
+description: \"A new Flutter project.\"
+
+dependencies:
+  flutter:
+    sdk: flutter
+
+  # The following adds the Cupertino Icons font to your application.
+  cupertino_icons: ^1.0.8
+  dio: ^5.3.3
+
+dev_dependencies:
+  flutter_test:
+    sdk: flutter
+  build_runner: ^2.4.6
+
+# The following section is specific to Flutter packages.
+flutter:
+
+  uses-material-design: true
+  assets:
+    - assets/images/
+    - assets/i18n/
+  fonts:
+    - family: AppIcons
+      fonts:
+        - asset: assets/fonts/AppIcons.ttf
+
+  # To add assets to your application, add an assets section, like this:
+  # assets:
+  #   - images/a_dot_burr.jpeg
+");
+
+        // Editing the result again changes nothing
+        let mut again = pubspec.clone();
+        assert!(!again.add_dependency("dio", "^5.3.3").unwrap());
+        assert!(!again.add_asset("assets/i18n/").unwrap());
+        assert_eq!(again.to_string(), pubspec.to_string());
+    }
+
+    #[test]
+    fn test_missing_and_inline_sections() {
+        let mut pubspec = pubspec("name: shop\nflutter: {}\n");
+        pubspec.add_dependency("dio", "^5.3.3").unwrap();
+        pubspec.add_asset("assets/").unwrap();
+        assert_eq!(pubspec.to_string(), "name: shop\nflutter:\n  assets:\n    - assets/\n\ndependencies:\n  dio: ^5.3.3\n");
+
+        // Items at the key's own indentation belong to the list
+        let mut pubspec = self::pubspec("flutter:\n  assets:\n  - assets/\n");
+        assert!(!pubspec.add_asset("assets/").unwrap());
+
+        let mut pubspec = self::pubspec("name: shop\ndependencies: {dio: ^5.0.0}\n");
+        assert!(pubspec.add_dependency("http", "^1.1.0").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+// regions.rs
+// Hand-written regions and checksums that let generated files be regenerated safely
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+/// Marker opening a region whose contents are kept when a file is regenerated
+pub const KEEP_START: &str = "// flutter_lazy:keep-start";
+
+/// Marker closing a keep region
+pub const KEEP_END: &str = "// flutter_lazy:keep-end";
+
+/// First line of generated files, recording the checksum of their generated parts
+const CHECKSUM_PREFIX: &str = "// flutter_lazy:checksum ";
+
+/// An empty keep region for generated code, indented to match its surroundings
+pub fn keep_region(indent: &str, id: &str) -> String {
+    format!("{0}{1} {2}\n{0}{3} {2}\n", indent, KEEP_START, id, KEEP_END)
+}
+
+/// Id of a keep region marker line, if the line is one
+fn marker_id<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    line.trim().strip_prefix(marker).map(str::trim)
+}
+
+/// Contents of every keep region by id, in file order
+fn keep_regions(content: &str) -> Result<IndexMap<String, String>> {
+    let mut regions = IndexMap::new();
+    let mut open: Option<(String, String)> = None;
+
+    for (number, line) in content.lines().enumerate() {
+        if let Some(id) = marker_id(line, KEEP_START) {
+            if let Some((open_id, _)) = &open {
+                return Err(anyhow::anyhow!(
+                    "Keep region '{}' starts on line {} before region '{}' is closed",
+                    id, number + 1, open_id
+                ));
+            }
+            if id.is_empty() || regions.contains_key(id) {
+                return Err(anyhow::anyhow!("Keep region on line {} needs a unique id", number + 1));
+            }
+            open = Some((id.to_string(), String::new()));
+        } else if let Some(id) = marker_id(line, KEEP_END) {
+            match open.take() {
+                Some((open_id, body)) if id.is_empty() || id == open_id => {
+                    regions.insert(open_id, body);
+                },
+                _ => return Err(anyhow::anyhow!("Unexpected end of keep region on line {}", number + 1)),
+            }
+        } else if let Some((_, body)) = &mut open {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+
+    match open {
+        Some((id, _)) => Err(anyhow::anyhow!("Keep region '{}' is never closed", id)),
+        None => Ok(regions),
+    }
+}
+
+/// Put hand-written region contents back into freshly generated content,
+/// returning the ids of non-empty regions the new content no longer has
+fn fill_keep_regions(generated: &str, regions: &IndexMap<String, String>) -> (String, Vec<String>) {
+    let mut result = String::new();
+    let mut used = Vec::new();
+    let mut skipping = false;
+
+    for line in generated.lines() {
+        if let Some(id) = marker_id(line, KEEP_START) {
+            result.push_str(line);
+            result.push('\n');
+            if let Some(body) = regions.get(id) {
+                result.push_str(body);
+                used.push(id.to_string());
+                skipping = true;
+            }
+            continue;
+        }
+        if marker_id(line, KEEP_END).is_some() {
+            skipping = false;
+        }
+        if !skipping {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    let dropped = regions.iter()
+        .filter(|(id, body)| !used.contains(id) && !body.trim().is_empty())
+        .map(|(id, _)| id.clone())
+        .collect();
+    (result, dropped)
+}
+
+/// Content without its checksum line
+fn strip_checksum(content: &str) -> &str {
+    match content.strip_prefix(CHECKSUM_PREFIX) {
+        Some(rest) => rest.split_once('\n').map_or("", |(_, body)| body),
+        None => content,
+    }
+}
+
+/// FNV-1a hash of everything outside keep regions, so edits inside them don't count
+fn checksum(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut inside = false;
+
+    for line in strip_checksum(content).lines() {
+        if marker_id(line, KEEP_END).is_some() {
+            inside = false;
+        }
+        if !inside {
+            for byte in line.trim_end().bytes().chain(std::iter::once(b'\n')) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        if marker_id(line, KEEP_START).is_some() {
+            inside = true;
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// Whether the generated parts of a tracked file still match its checksum
+pub fn is_unedited(content: &str) -> bool {
+    content
+        .strip_prefix(CHECKSUM_PREFIX)
+        .and_then(|rest| rest.lines().next())
+        .is_some_and(|recorded| recorded.trim() == checksum(content))
+}
+
+/// Content to write for a generated file, given what is on disk now
+///
+/// Keep regions of the existing file are carried over. Without `force`, files
+/// whose generated parts were edited by hand (or that flutter_lazy did not
+/// write) are refused, as are regenerations that would drop a keep region.
+/// With `force`, keep regions the existing file has broken markers for are dropped.
+pub fn merge_generated(existing: Option<&str>, generated: &str, force: bool) -> Result<String> {
+    let generated = strip_checksum(generated);
+
+    let merged = match existing {
+        Some(existing) => {
+            if !force && !is_unedited(existing) {
+                return Err(anyhow::anyhow!(
+                    "Generated code was edited outside of flutter_lazy:keep regions; move the changes into a keep region or use --force to overwrite them"
+                ));
+            }
+
+            let regions = match keep_regions(existing) {
+                Ok(regions) => regions,
+                Err(_) if force => IndexMap::new(),
+                Err(error) => return Err(error),
+            };
+            let (merged, dropped) = fill_keep_regions(generated, &regions);
+            if !force && !dropped.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Keep regions {} no longer exist in the generated code; use --force to drop them",
+                    dropped.join(", ")
+                ));
+            }
+            merged
+        },
+        None => generated.to_string(),
+    };
+
+    Ok(format!("{}{}\n{}", CHECKSUM_PREFIX, checksum(&merged), merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generated(body: &str) -> String {
+        format!("class Pet {{\n  final String name;\n{}{}}}\n", keep_region("  ", "members"), body)
+    }
+
+    #[test]
+    fn test_keep_regions_survive_regeneration() {
+        let first = merge_generated(None, &generated(""), false).unwrap();
+        assert!(first.starts_with(CHECKSUM_PREFIX) && is_unedited(&first));
+
+        // Hand-written code inside the region does not count as an edit
+        let edited = first.replace(
+            "  // flutter_lazy:keep-end members",
+            "  String get label => name;\n  // flutter_lazy:keep-end members"
+        );
+        assert!(is_unedited(&edited));
+
+        let second = merge_generated(Some(&edited), &generated("  // new field\n"), false).unwrap();
+        assert!(second.contains("keep-start members\n  String get label => name;\n  // flutter_lazy:keep-end members"), "{}", second);
+        assert!(second.contains("// new field"));
+        assert!(is_unedited(&second));
+    }
+
+    #[test]
+    fn test_edited_files_are_refused() {
+        let first = merge_generated(None, &generated(""), false).unwrap();
+        let edited = first.replace("final String name;", "final String? name;");
+        assert!(!is_unedited(&edited));
+        assert!(merge_generated(Some(&edited), &generated(""), false).is_err());
+        assert!(merge_generated(Some(&edited), &generated(""), true).is_ok());
+
+        // Files flutter_lazy did not write are never overwritten silently
+        assert!(merge_generated(Some("class Pet {}\n"), &generated(""), false).is_err());
+
+        // Nor are hand-written keep regions dropped
+        let without_region = "class Pet {\n  final String name;\n}\n";
+        assert!(merge_generated(Some(&first), without_region, false).is_ok());
+        let filled = first.replace("  // flutter_lazy:keep-end", "  int get age => 1;\n  // flutter_lazy:keep-end");
+        assert!(merge_generated(Some(&filled), without_region, false).is_err());
+        assert!(keep_regions("// flutter_lazy:keep-start a\n").is_err());
+
+        // Broken keep region markers are only overwritten with --force
+        let unclosed = first.replace("  // flutter_lazy:keep-end members\n", "");
+        assert!(merge_generated(Some(&unclosed), &generated(""), false).is_err());
+        assert!(merge_generated(Some(&unclosed), &generated(""), true).is_ok());
+    }
+}
@@ -2,15 +2,25 @@
 // Module for parsing Swagger/OpenAPI specs and generating features
 
 use std::path::{Path, PathBuf};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use anyhow::{Context, Result};
 use convert_case::{Case, Casing};
-use serde::Deserialize;
-use reqwest;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 use console::style;
 
+use crate::dependencies::{self, Packages};
 use crate::features::{FeatureParams, create_feature};
+use crate::regions::keep_region;
+use crate::templates::{FeatureContext, ProjectContext, TemplateContext};
+use crate::utils::{generate_template_file, remove_generated_file, render_template, write_generated_file};
+use crate::vfs;
+
+mod diff;
+
+pub use diff::diff_api_specs;
 
 /// Source of Swagger/OpenAPI specification
 #[derive(Clone)]
@@ -19,7 +29,12 @@ pub enum SwaggerSource {
     File(PathBuf),
 }
 
-/// Simple representation of a Swagger/OpenAPI spec
+/// Representation of a Swagger 2.0 or OpenAPI 3.x spec
+///
+/// Both versions are read into the same model: Swagger 2.0 keeps its shared
+/// objects at the top level (`definitions`, `parameters`, `responses`) while
+/// OpenAPI 3 keeps them under `components`. Use the `resolve_*` methods to
+/// follow local `$ref`s in either layout.
 #[derive(Debug, Deserialize)]
 struct SwaggerSpec {
     #[serde(default)]
@@ -27,9 +42,13 @@ struct SwaggerSpec {
     #[serde(default)]
     tags: Vec<SwaggerTag>,
     #[serde(default)]
-    paths: HashMap<String, HashMap<String, SwaggerOperation>>,
+    paths: IndexMap<String, SwaggerPathItem>,
     #[serde(default, alias = "definitions")]
-    schemas: HashMap<String, SwaggerSchema>,
+    schemas: IndexMap<String, SwaggerSchema>,
+    #[serde(default)]
+    parameters: IndexMap<String, SwaggerParameter>,
+    #[serde(default)]
+    responses: IndexMap<String, SwaggerResponse>,
     #[serde(default)]
     components: Option<SwaggerComponents>,
 }
@@ -37,7 +56,13 @@ struct SwaggerSpec {
 #[derive(Debug, Default, Deserialize)]
 struct SwaggerComponents {
     #[serde(default)]
-    schemas: HashMap<String, SwaggerSchema>,
+    schemas: IndexMap<String, SwaggerSchema>,
+    #[serde(default)]
+    parameters: IndexMap<String, SwaggerParameter>,
+    #[serde(default)]
+    responses: IndexMap<String, SwaggerResponse>,
+    #[serde(default, rename = "requestBodies")]
+    request_bodies: IndexMap<String, SwaggerRequestBody>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -55,6 +80,40 @@ struct SwaggerTag {
     description: String,
 }
 
+/// Operations of a single path, plus parameters shared by all of them
+#[derive(Debug, Default, Deserialize)]
+struct SwaggerPathItem {
+    #[serde(default)]
+    parameters: Vec<SwaggerParameter>,
+    get: Option<SwaggerOperation>,
+    put: Option<SwaggerOperation>,
+    post: Option<SwaggerOperation>,
+    delete: Option<SwaggerOperation>,
+    options: Option<SwaggerOperation>,
+    head: Option<SwaggerOperation>,
+    patch: Option<SwaggerOperation>,
+    trace: Option<SwaggerOperation>,
+}
+
+impl SwaggerPathItem {
+    /// Operations defined on this path as (lowercase method, operation) pairs
+    fn operations(&self) -> Vec<(&'static str, &SwaggerOperation)> {
+        [
+            ("get", &self.get),
+            ("put", &self.put),
+            ("post", &self.post),
+            ("delete", &self.delete),
+            ("options", &self.options),
+            ("head", &self.head),
+            ("patch", &self.patch),
+            ("trace", &self.trace),
+        ]
+        .into_iter()
+        .filter_map(|(method, operation)| operation.as_ref().map(|op| (method, op)))
+        .collect()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct SwaggerOperation {
     #[serde(default)]
@@ -62,53 +121,143 @@ struct SwaggerOperation {
     #[serde(default)]
     summary: String,
     #[serde(default)]
+    #[allow(dead_code)]
     description: String,
     #[serde(default, rename = "operationId")]
     operation_id: String,
     #[serde(default)]
     parameters: Vec<SwaggerParameter>,
+    #[serde(default, rename = "requestBody")]
+    request_body: Option<SwaggerRequestBody>,
     #[serde(default, rename = "responses")]
-    responses: HashMap<String, SwaggerResponse>,
+    responses: IndexMap<String, SwaggerResponse>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 struct SwaggerParameter {
+    #[serde(default, rename = "ref")]
+    #[serde(alias = "$ref")]
+    reference: Option<String>,
     #[serde(default)]
     name: String,
     #[serde(default)]
-    r#in: String,  // "path", "query", "header", "body"
+    r#in: String,  // "path", "query", "header", "cookie", "body" (2.0), "formData" (2.0)
     #[serde(default)]
     required: bool,
     #[serde(default)]
     schema: Option<SwaggerSchema>,
+    // Swagger 2.0 declares the type of non-body parameters inline
+    #[serde(default, deserialize_with = "deserialize_schema_type")]
+    r#type: String,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    items: Option<Box<SwaggerSchema>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 struct SwaggerResponse {
+    #[serde(default, rename = "ref")]
+    #[serde(alias = "$ref")]
+    reference: Option<String>,
     #[serde(default)]
+    #[allow(dead_code)]
     description: String,
+    // Swagger 2.0
     #[serde(default)]
     schema: Option<SwaggerSchema>,
+    // OpenAPI 3
+    #[serde(default)]
+    content: IndexMap<String, SwaggerMediaType>,
 }
 
+/// OpenAPI 3 request body
 #[derive(Debug, Deserialize, Clone)]
-struct SwaggerSchema {
+struct SwaggerRequestBody {
+    #[serde(default, rename = "ref")]
+    #[serde(alias = "$ref")]
+    reference: Option<String>,
     #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    content: IndexMap<String, SwaggerMediaType>,
+}
+
+/// OpenAPI 3 media type object (an entry of a `content` map)
+#[derive(Debug, Deserialize, Clone)]
+struct SwaggerMediaType {
+    #[serde(default)]
+    schema: Option<SwaggerSchema>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct SwaggerSchema {
+    #[serde(default, deserialize_with = "deserialize_schema_type")]
     r#type: String,
+    #[serde(default)]
+    format: Option<String>,
     #[serde(default, rename = "ref")]
     #[serde(alias = "$ref")]
     reference: Option<String>,
     #[serde(default)]
-    properties: HashMap<String, SwaggerProperty>,
+    properties: IndexMap<String, SwaggerProperty>,
+    #[serde(default)]
+    items: Option<Box<SwaggerSchema>>,
     #[serde(default)]
     required: Vec<String>,
     #[serde(default)]
     description: String,
+    #[serde(default, rename = "allOf")]
+    all_of: Vec<SwaggerSchema>,
+    #[serde(default, rename = "oneOf")]
+    one_of: Vec<SwaggerSchema>,
+    #[serde(default, rename = "anyOf")]
+    any_of: Vec<SwaggerSchema>,
+    #[serde(default)]
+    discriminator: Option<SwaggerDiscriminator>,
+    #[serde(default, rename = "enum")]
+    enum_values: Vec<serde_json::Value>,
+}
+
+/// Property telling polymorphic schemas apart: a plain property name in
+/// Swagger 2.0, an object with an optional value mapping in OpenAPI 3
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+enum SwaggerDiscriminator {
+    PropertyName(String),
+    Object {
+        #[serde(rename = "propertyName")]
+        property_name: String,
+        #[serde(default)]
+        mapping: IndexMap<String, String>,
+    },
+}
+
+impl SwaggerDiscriminator {
+    fn property_name(&self) -> &str {
+        match self {
+            SwaggerDiscriminator::PropertyName(name) => name,
+            SwaggerDiscriminator::Object { property_name, .. } => property_name,
+        }
+    }
+
+    /// Discriminator value for a variant schema; defaults to the schema name
+    fn value_for(&self, schema_name: &str) -> String {
+        if let SwaggerDiscriminator::Object { mapping, .. } = self {
+            let mapped = mapping
+                .iter()
+                .find(|(_, reference)| schema_ref_name(reference).as_deref() == Some(schema_name));
+            if let Some((value, _)) = mapped {
+                return value.clone();
+            }
+        }
+        schema_name.to_string()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 struct SwaggerProperty {
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_schema_type")]
     r#type: String,
     #[serde(default)]
     format: Option<String>,
@@ -119,14 +268,243 @@ struct SwaggerProperty {
     items: Option<Box<SwaggerSchema>>,
     #[serde(default)]
     description: String,
+    // A single `allOf` entry is a common way to add a description to a reference
+    #[serde(default, rename = "allOf")]
+    all_of: Vec<SwaggerSchema>,
+    #[serde(default, rename = "enum")]
+    enum_values: Vec<serde_json::Value>,
+}
+
+/// Accept both `type: string` and the OpenAPI 3.1 form `type: [string, "null"]`
+fn deserialize_schema_type<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SchemaType {
+        Single(String),
+        Multiple(Vec<String>),
+    }
+
+    Ok(match SchemaType::deserialize(deserializer)? {
+        SchemaType::Single(t) => t,
+        SchemaType::Multiple(types) => types.into_iter().find(|t| t != "null").unwrap_or_default(),
+    })
+}
+
+/// Packages the generated API code imports beyond the feature templates
+pub const API_PACKAGES: Packages = Packages {
+    dependencies: &["dartz", "dio", "injectable", "intl", "json_annotation", "shared_preferences"],
+    dev_dependencies: &["build_runner", "injectable_generator", "json_serializable"],
+};
+
+/// Local reference prefixes for each kind of shared object, Swagger 2.0 first
+const SCHEMA_REF_PREFIXES: [&str; 2] = ["#/definitions/", "#/components/schemas/"];
+const PARAMETER_REF_PREFIXES: [&str; 2] = ["#/parameters/", "#/components/parameters/"];
+const RESPONSE_REF_PREFIXES: [&str; 2] = ["#/responses/", "#/components/responses/"];
+const REQUEST_BODY_REF_PREFIXES: [&str; 1] = ["#/components/requestBodies/"];
+
+/// Media types treated as JSON, in order of preference
+const JSON_MEDIA_TYPES: [&str; 2] = ["application/json", "*/*"];
+
+/// Media types of form request bodies, in order of preference
+const FORM_MEDIA_TYPES: [&str; 2] = ["multipart/form-data", "application/x-www-form-urlencoded"];
+
+/// Extract the object name from a local reference with one of the given prefixes
+fn local_ref_name<'a>(reference: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|prefix| reference.strip_prefix(prefix))
+}
+
+/// Extract the model name from a schema reference
+/// (e.g. "#/definitions/User" or "#/components/schemas/User" -> "User")
+fn schema_ref_name(reference: &str) -> Option<String> {
+    if let Some(name) = local_ref_name(reference, &SCHEMA_REF_PREFIXES) {
+        return Some(name.to_string());
+    }
+
+    // Bundled specs only contain local references, but fall back to the name
+    // the schema has in the referenced file ("common.yaml#/User" -> "User")
+    let (location, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+    match pointer.rsplit('/').next().filter(|name| !name.is_empty()) {
+        Some(name) => Some(name.to_string()),
+        None => Path::new(location)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| stem.to_case(Case::Pascal)),
+    }
+}
+
+impl SwaggerSpec {
+    /// Look up a shared object in either the Swagger 2.0 or OpenAPI 3 location
+    fn lookup<'a, T>(
+        reference: &str,
+        prefixes: &[&str],
+        swagger2: &'a IndexMap<String, T>,
+        openapi3: Option<&'a IndexMap<String, T>>,
+    ) -> Result<&'a T> {
+        let name = local_ref_name(reference, prefixes)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported reference: {}", reference))?;
+
+        swagger2.get(name)
+            .or_else(|| openapi3.and_then(|objects| objects.get(name)))
+            .ok_or_else(|| anyhow::anyhow!("Unresolved reference: {}", reference))
+    }
+
+    /// Follow `#/definitions/...` or `#/components/schemas/...` to the named schema
+    fn resolve_schema(&self, reference: &str) -> Result<&SwaggerSchema> {
+        let components = self.components.as_ref().map(|c| &c.schemas);
+        Self::lookup(reference, &SCHEMA_REF_PREFIXES, &self.schemas, components)
+    }
+
+    /// All named schemas from `definitions` and `components/schemas`
+    fn all_schemas(&self) -> impl Iterator<Item = (&String, &SwaggerSchema)> {
+        self.components.iter()
+            .flat_map(|components| components.schemas.iter())
+            .chain(self.schemas.iter())
+    }
+
+    /// Follow a chain of local references to the object it ends at, failing on cycles
+    fn follow_refs<'a, T: Clone>(
+        object: &'a T,
+        reference: impl Fn(&T) -> Option<&String>,
+        lookup: impl Fn(&str) -> Result<&'a T>,
+    ) -> Result<T> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = object;
+        while let Some(next) = reference(current) {
+            if let Some(start) = chain.iter().position(|seen| seen == next) {
+                let cycle = chain[start..].iter().copied().chain(std::iter::once(next.as_str()));
+                return Err(anyhow::anyhow!("Circular $ref: {}", cycle.collect::<Vec<_>>().join(" -> ")));
+            }
+            chain.push(next);
+            current = lookup(next)?;
+        }
+        Ok(current.clone())
+    }
+
+    /// Follow `#/parameters/...` or `#/components/parameters/...`
+    fn resolve_parameter(&self, parameter: &SwaggerParameter) -> Result<SwaggerParameter> {
+        let components = self.components.as_ref().map(|c| &c.parameters);
+        Self::follow_refs(parameter, |parameter| parameter.reference.as_ref(), |reference| {
+            Self::lookup(reference, &PARAMETER_REF_PREFIXES, &self.parameters, components)
+        })
+    }
+
+    /// Follow `#/responses/...` or `#/components/responses/...`
+    fn resolve_response(&self, response: &SwaggerResponse) -> Result<SwaggerResponse> {
+        let components = self.components.as_ref().map(|c| &c.responses);
+        Self::follow_refs(response, |response| response.reference.as_ref(), |reference| {
+            Self::lookup(reference, &RESPONSE_REF_PREFIXES, &self.responses, components)
+        })
+    }
+
+    /// Follow `#/components/requestBodies/...`
+    fn resolve_request_body(&self, body: &SwaggerRequestBody) -> Result<SwaggerRequestBody> {
+        let components = self.components.as_ref().map(|c| &c.request_bodies);
+        let empty = IndexMap::new();
+        Self::follow_refs(body, |body| body.reference.as_ref(), |reference| {
+            Self::lookup(reference, &REQUEST_BODY_REF_PREFIXES, &empty, components)
+        })
+    }
+
+    /// Resolved parameters of an operation, including those declared on its path
+    ///
+    /// An OpenAPI 3 `requestBody` is returned as a Swagger 2.0 style `in: body`
+    /// parameter so the generators only have to handle one shape.
+    fn operation_parameters(&self, path_item: &SwaggerPathItem, operation: &SwaggerOperation) -> Result<Vec<SwaggerParameter>> {
+        let mut parameters: Vec<SwaggerParameter> = Vec::new();
+
+        // Operation parameters override path parameters with the same name and location
+        for parameter in path_item.parameters.iter().chain(&operation.parameters) {
+            let resolved = self.resolve_parameter(parameter)?;
+            parameters.retain(|p| !(p.name == resolved.name && p.r#in == resolved.r#in));
+            parameters.push(resolved);
+        }
+
+        if let Some(body) = &operation.request_body {
+            let body = self.resolve_request_body(body)?;
+            if let Some(schema) = json_schema(&body.content) {
+                parameters.push(SwaggerParameter {
+                    reference: None,
+                    name: "body".to_string(),
+                    r#in: "body".to_string(),
+                    required: body.required,
+                    schema: Some(schema.clone()),
+                    r#type: String::new(),
+                    format: None,
+                    items: None,
+                });
+            } else if let Some(schema) = form_schema(&body.content) {
+                // Form fields become Swagger 2.0 style `in: formData` parameters
+                let schema = match &schema.reference {
+                    Some(reference) => self.resolve_schema(reference)?,
+                    None => schema,
+                };
+                for (name, property) in &schema.properties {
+                    parameters.push(SwaggerParameter {
+                        reference: None,
+                        name: name.clone(),
+                        r#in: "formData".to_string(),
+                        required: schema.required.contains(name),
+                        schema: None,
+                        r#type: property.r#type.clone(),
+                        format: property.format.clone(),
+                        items: property.items.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(parameters)
+    }
+}
+
+/// Pick the form schema (multipart or URL-encoded) from an OpenAPI 3 `content` map
+fn form_schema(content: &IndexMap<String, SwaggerMediaType>) -> Option<&SwaggerSchema> {
+    FORM_MEDIA_TYPES.iter()
+        .find_map(|media_type| content.get(*media_type))?
+        .schema.as_ref()
+}
+
+/// Pick the JSON schema from an OpenAPI 3 `content` map
+fn json_schema(content: &IndexMap<String, SwaggerMediaType>) -> Option<&SwaggerSchema> {
+    let preferred = JSON_MEDIA_TYPES.iter().find_map(|media_type| content.get(*media_type));
+
+    // Fall back to vendor JSON types such as application/problem+json
+    let vendor_json = || {
+        let mut json_types: Vec<&String> = content.keys()
+            .filter(|media_type| media_type.ends_with("/json") || media_type.ends_with("+json"))
+            .collect();
+        json_types.sort();
+        json_types.first().and_then(|media_type| content.get(*media_type))
+    };
+
+    preferred.or_else(vendor_json)?.schema.as_ref()
+}
+
+/// What API generation does with the features and generated files a project already has
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpdateMode {
+    /// Keep existing features and only regenerate the files generated from the spec
+    pub update: bool,
+    /// Overwrite and remove generated files even if they were edited outside of keep regions
+    pub force: bool,
 }
 
 /// Generate features based on Swagger/OpenAPI specification
+///
+/// With `mode.update`, features that already exist are kept and only the files
+/// generated from the spec (models, entities, the remote datasource and the
+/// repository) are regenerated, followed by a summary of what the spec changed.
 pub fn generate_api_features(
     project_dir: &Path,
     source: SwaggerSource,
     domain_filter: Option<Vec<String>>,
     data_only: bool,
+    type_mappings: &TypeMappings,
+    grouping: GroupingStrategy,
+    mode: UpdateMode,
 ) -> Result<()> {
     // Load the Swagger spec
     let spec = load_swagger_spec(source)?;
@@ -134,260 +512,1298 @@ pub fn generate_api_features(
     println!("Loaded API: {} (v{})", style(&spec.info.title).bold(), spec.info.version);
     
     // Extract all tags/domains
-    let mut domains = extract_domains(&spec)?;
+    let all_domains = extract_domains(&spec, grouping)?;
     
     // Apply filter if provided
-    if let Some(filter) = domain_filter {
-        domains.retain(|domain| filter.contains(&domain.name));
-    }
+    let domains: Vec<&Domain> = all_domains.iter()
+        .filter(|domain| domain_filter.as_ref().is_none_or(|filter| filter.contains(&domain.name)))
+        .collect();
     
     println!("Found {} domains/tags in the API", style(domains.len()).bold());
     
     // Extract schemas for model generation
-    let schemas = extract_schemas(&spec)?;
+    let schemas = extract_schemas(&spec, type_mappings)?;
     println!("Found {} data models in API schemas", style(schemas.len()).bold());
     
+    dependencies::add_to_project(project_dir, &[API_PACKAGES])?;
+    
+    let previous = ApiSnapshot::load(project_dir)?;
+    let mut snapshot = ApiSnapshot {
+        schemas: schemas.iter().map(|(name, schema)| (name.clone(), schema_fingerprint(schema))).collect(),
+        ..ApiSnapshot::default()
+    };
+    
+    // Place every schema the domains reach; ones used by several domains are shared
+    let layout = ModelLayout::new(&schemas, &all_domains).select(&domains);
+    if !layout.members(&ModelGroup::Shared).is_empty() {
+        println!("\n🔹 Generating shared models");
+        snapshot.files.extend(generate_models(project_dir, &ModelGroup::Shared, &layout, mode.force)?);
+    }
+    
     // Generate features for each domain
-    for domain in domains {
+    for domain in &domains {
         println!("\n🔹 Generating feature for domain: {}", style(&domain.name).bold());
         
         // Create a feature name from the domain
         let feature_name = domain.name.to_case(Case::Snake);
         
-        // Set up feature parameters
-        let mut params = FeatureParams::new(&feature_name);
-        
-        // If data_only is true, disable state management (cubit)
-        if data_only {
-            params.has_state_management = false;
+        // Create the base feature structure, unless updating one that exists
+        let feature_dir = project_dir.join("lib/features").join(&feature_name);
+        if mode.update && vfs::exists(&feature_dir) {
+            println!("  Updating existing feature {}", style(&feature_name).bold());
+        } else {
+            // Set up feature parameters
+            let mut params = FeatureParams::new(&feature_name);
+            
+            // If data_only is true, disable state management (cubit)
+            if data_only {
+                params.has_state_management = false;
+            }
+            
+            create_feature(project_dir, params)?;
+            
+            // The generated data layer replaces the scaffolded repository, and the
+            // scaffolded model when a schema is named after the feature
+            let group = ModelGroup::Feature(feature_name.clone());
+            let mut scaffolds = vec![feature_dir.join("data/repository").join(format!("{}_repository.dart", feature_name))];
+            scaffolds.extend(layout.members(&group).into_iter()
+                .filter(|schema| !schema.is_enum())
+                .map(|schema| project_dir.join(layout.model_file(&schema.name)))
+                .filter(|file| file.ends_with(format!("{}_model.dart", feature_name))));
+            for scaffold in scaffolds.into_iter().filter(|file| vfs::exists(file)) {
+                vfs::remove_file(&scaffold).with_context(|| format!("Failed to remove {}", scaffold.display()))?;
+            }
         }
         
-        // Create the base feature structure
-        create_feature(project_dir, params)?;
-        
-        // Generate model classes based on response schemas used in this domain
-        generate_domain_models(project_dir, &feature_name, &domain, &schemas)?;
+        // Generate model classes for the schemas only this domain uses
+        snapshot.files.extend(generate_models(project_dir, &ModelGroup::Feature(feature_name.clone()), &layout, mode.force)?);
         
         // Generate the datasource and repository implementations
-        generate_domain_datasources(project_dir, &feature_name, &domain, &spec)?;
+        snapshot.files.extend(generate_domain_datasources(project_dir, &feature_name, domain, type_mappings, &layout, mode.force)?);
+        snapshot.record_domain(&feature_name, domain, type_mappings);
     }
     
-    Ok(())
-}
-
-/// Load Swagger spec from URL or file
-fn load_swagger_spec(source: SwaggerSource) -> Result<SwaggerSpec> {
-    match source {
-        SwaggerSource::Url(url) => {
-            // Make HTTP request to fetch the spec
-            println!("Fetching Swagger specification from URL...");
-            
-            let response = reqwest::blocking::get(&url)
-                .context(format!("Failed to fetch Swagger spec from {}", &url))?;
-            
-            if !response.status().is_success() {
-                return Err(anyhow::anyhow!(
-                    "Failed to fetch Swagger spec, status code: {}", 
-                    response.status()
-                ));
+    let regenerated: BTreeSet<String> = snapshot.endpoints.keys().cloned().collect();
+    let mut features = regenerated.clone();
+    if let Some(previous) = &previous {
+        // A full update covers every feature; otherwise features left out keep their record
+        let full_update = mode.update && domain_filter.is_none();
+        for (feature, endpoints) in &previous.endpoints {
+            if full_update {
+                features.insert(feature.clone());
+            } else {
+                snapshot.endpoints.entry(feature.clone()).or_insert_with(|| endpoints.clone());
             }
-            
-            let spec: SwaggerSpec = response.json()
-                .context("Failed to parse Swagger JSON")?;
-            
-            Ok(spec)
-        },
-        SwaggerSource::File(path) => {
-            // Read the file content
-            println!("Reading Swagger specification from file...");
-            
-            let file_content = fs::read_to_string(&path)
-                .context(format!("Failed to read Swagger file: {:?}", path))?;
-            
-            let spec: SwaggerSpec = serde_json::from_str(&file_content)
-                .context("Failed to parse Swagger JSON")?;
-            
-            Ok(spec)
         }
-    }
-}
-
-/// Extract all domains/tags from the Swagger spec
-/// A domain is represented by a tag in the Swagger spec
-struct Domain {
-    name: String,
-    description: String,
-    endpoints: Vec<Endpoint>,
-}
-
-#[derive(Clone)]
-struct Endpoint {
-    path: String,
-    method: String,
-    operation_id: String,
-    summary: String,
-    parameters: Vec<SwaggerParameter>,
-    response_type: Option<String>,
-}
-
-fn extract_domains(spec: &SwaggerSpec) -> Result<Vec<Domain>> {
-    // First, collect all tags as domains
-    let mut domains: HashMap<String, Domain> = spec.tags.iter()
-        .map(|tag| {
-            (tag.name.clone(), Domain {
-                name: tag.name.clone(),
-                description: tag.description.clone(),
-                endpoints: Vec::new(),
-            })
-        })
-        .collect();
-    
-    // Then associate each path/operation with its tag/domain
-    for (path, methods) in &spec.paths {
-        for (method, operation) in methods {
-            // Skip if no tags
-            if operation.tags.is_empty() {
+        
+        // Updates remove generated files the spec no longer produces
+        let dropped: Vec<PathBuf> = previous.files.difference(&snapshot.files).cloned().collect();
+        for file in dropped {
+            let stale = mode.update && (full_update || regenerated.iter().any(|feature| {
+                let group = ModelGroup::Feature(feature.clone());
+                file.starts_with(Path::new("lib/features").join(feature)) || file.starts_with(group.entities_dir())
+            }));
+            if !stale {
+                snapshot.files.insert(file);
                 continue;
             }
             
-            // Create endpoint
-            let endpoint = Endpoint {
-                path: path.clone(),
-                method: method.to_uppercase(),
-                operation_id: operation.operation_id.clone(),
-                summary: operation.summary.clone(),
-                parameters: operation.parameters.clone(),
-                response_type: extract_response_type(&operation.responses),
-            };
-            
-            // Add to each associated domain
-            for tag in &operation.tags {
-                if let Some(domain) = domains.get_mut(tag) {
-                    domain.endpoints.push(endpoint.clone());
-                } else {
-                    // Create a new domain if the tag wasn't defined in the tags section
-                    domains.insert(tag.clone(), Domain {
-                        name: tag.clone(),
-                        description: String::new(),
-                        endpoints: vec![endpoint.clone()],
-                    });
-                }
+            let path = project_dir.join(&file);
+            if vfs::exists(&path) {
+                remove_generated_file(&path, mode.force)?;
+                println!("  ✓ Removed {}", style(file.display()).bold());
             }
         }
     }
     
-    Ok(domains.into_values().collect())
-}
-
-/// Extract response type from operation responses
-fn extract_response_type(responses: &HashMap<String, SwaggerResponse>) -> Option<String> {
-    // Look for 200/201 responses first
-    for status in ["200", "201"] {
-        if let Some(response) = responses.get(status) {
-            if let Some(schema) = &response.schema {
-                if let Some(ref_str) = &schema.reference {
-                    // Extract the model name from the reference
-                    // Typical format: "#/definitions/User" or "#/components/schemas/User"
-                    if let Some(model_name) = ref_str.split('/').last() {
-                        return Some(model_name.to_string());
-                    }
-                }
-            }
+    if mode.update {
+        match &previous {
+            Some(previous) => print_api_changes(previous, &snapshot, &features),
+            None => println!(
+                "\n{} No record of the previous generation ({}), so changes cannot be summarized",
+                style("⚠️").yellow().bold(),
+                API_SNAPSHOT_FILE
+            ),
         }
     }
-    None
+    snapshot.save(project_dir)?;
+    
+    Ok(())
 }
 
-/// Extract schemas for model generation
-struct SchemaInfo {
-    name: String,
-    properties: HashMap<String, PropertyInfo>,
-    required: Vec<String>,
-    description: String,
+/// Record of the last API generation, kept to summarize what an update changes
+const API_SNAPSHOT_FILE: &str = ".flutter_lazy/api_snapshot.json";
+
+/// What was generated from the spec last time
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ApiSnapshot {
+    /// Dart signature of every endpoint by feature, keyed by `METHOD /path`
+    endpoints: BTreeMap<String, BTreeMap<String, String>>,
+    /// Shape of every schema in the spec
+    schemas: BTreeMap<String, String>,
+    /// Files regenerated from the spec, relative to the project root
+    files: BTreeSet<PathBuf>,
 }
 
-impl SchemaInfo {
-    fn has_date_time_field(&self) -> bool {
-        self.properties.values().any(|prop| prop.type_name == "dateTime")
+impl ApiSnapshot {
+    /// Load the project's snapshot; projects generated before snapshots existed have none
+    fn load(project_dir: &Path) -> Result<Option<Self>> {
+        let path = project_dir.join(API_SNAPSHOT_FILE);
+        if !vfs::exists(&path) {
+            return Ok(None);
+        }
+        
+        let content = vfs::read_to_string(&path)
+            .with_context(|| format!("Failed to read API snapshot: {}", path.display()))?;
+        let snapshot = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse API snapshot: {}", path.display()))?;
+        Ok(Some(snapshot))
     }
 
-    fn has_list_field(&self) -> bool {
-        self.properties.values().any(|prop| prop.is_list)
+    fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(API_SNAPSHOT_FILE);
+        if let Some(dir) = path.parent() {
+            vfs::create_dir_all(dir).context("Failed to create snapshot directory")?;
+        }
+        
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize API snapshot")?;
+        vfs::write(&path, content)
+            .with_context(|| format!("Failed to write API snapshot: {}", path.display()))
+    }
+
+    fn record_domain(&mut self, feature_name: &str, domain: &Domain, type_mappings: &TypeMappings) {
+        let endpoints = domain.endpoints.iter()
+            .map(|endpoint| {
+                let params = dart_parameters(endpoint, type_mappings);
+                let signature = format!(
+                    "{} {}({})",
+                    endpoint.response_signature(),
+                    endpoint.method_name,
+                    dart_parameter_list(&params)
+                );
+                (format!("{} {}", endpoint.method, endpoint.path), signature)
+            })
+            .collect();
+        self.endpoints.insert(feature_name.to_string(), endpoints);
     }
 }
 
-struct PropertyInfo {
-    name: String,
-    type_name: String,
-    is_list: bool,
-    is_nullable: bool,
-    description: String,
-    reference: Option<String>,
+/// Short description of a schema's shape, to tell whether it changed
+fn schema_fingerprint(schema: &SchemaInfo) -> String {
+    let mut parts: Vec<String> = schema.properties.iter()
+        .map(|(name, prop)| format!(
+            "{}: {}{}",
+            name,
+            prop.type_name,
+            if prop.is_nullable { "?" } else { "" }
+        ))
+        .collect();
+    parts.extend(schema.variants.iter().map(|variant| format!("{} => {}", variant.value, variant.schema)));
+    parts.extend(schema.enums.iter().map(|info| format!("enum {} {:?}", info.name, info.values)));
+    parts.join(", ")
 }
 
-fn extract_schemas(spec: &SwaggerSpec) -> Result<HashMap<String, SchemaInfo>> {
-    let mut result = HashMap::new();
-    
-    // In OpenAPI 3.0, schemas are under components/schemas
-    if let Some(components) = &spec.components {
-        for (name, schema) in &components.schemas {
-            let schema_info = extract_schema_info(name, schema);
-            result.insert(name.clone(), schema_info);
-        }
-    }
-    
-    // In Swagger 2.0, schemas are under definitions
-    for (name, schema) in &spec.schemas {
-        let schema_info = extract_schema_info(name, schema);
-        result.insert(name.clone(), schema_info);
-    }
-    
-    Ok(result)
+/// Keys added, changed and removed between two maps
+fn changes<'a, V: PartialEq>(
+    previous: &'a BTreeMap<String, V>,
+    current: &'a BTreeMap<String, V>,
+) -> (Vec<&'a str>, Vec<&'a str>, Vec<&'a str>) {
+    let added = current.keys().filter(|key| !previous.contains_key(*key)).map(String::as_str).collect();
+    let changed = current.iter()
+        .filter(|(key, value)| previous.get(*key).is_some_and(|old| old != *value))
+        .map(|(key, _)| key.as_str())
+        .collect();
+    let removed = previous.keys().filter(|key| !current.contains_key(*key)).map(String::as_str).collect();
+    (added, changed, removed)
 }
 
-fn extract_schema_info(name: &str, schema: &SwaggerSchema) -> SchemaInfo {
-    let mut properties = HashMap::new();
+/// Print the endpoints and schemas an update added, changed and removed
+fn print_api_changes(previous: &ApiSnapshot, current: &ApiSnapshot, features: &BTreeSet<String>) {
+    let empty = BTreeMap::new();
+    let mut endpoint_lines = Vec::new();
+    let mut counts = (0, 0, 0);
+    for feature in features {
+        let (added, changed, removed) = changes(
+            previous.endpoints.get(feature).unwrap_or(&empty),
+            current.endpoints.get(feature).unwrap_or(&empty),
+        );
+        counts = (counts.0 + added.len(), counts.1 + changed.len(), counts.2 + removed.len());
+        endpoint_lines.extend(added.iter().map(|key| format!("    + {}: {}", feature, key)));
+        endpoint_lines.extend(changed.iter().map(|key| format!("    ~ {}: {}", feature, key)));
+        endpoint_lines.extend(removed.iter().map(|key| format!("    - {}: {}", feature, key)));
+    }
     
-    for (prop_name, prop) in &schema.properties {
-        let type_name = determine_property_type(prop);
-        let is_list = prop.r#type == "array";
-        let is_nullable = !schema.required.contains(prop_name);
-        
-        let reference = if let Some(ref_path) = &prop.reference {
-            // Extract model name from reference (e.g., "#/definitions/User" -> "User")
-            ref_path.split('/').last().map(|s| s.to_string())
-        } else if let Some(items) = &prop.items {
-            // For array items with a reference
-            items.reference.as_ref().and_then(|ref_path| {
-                ref_path.split('/').last().map(|s| s.to_string())
-            })
-        } else {
-            None
-        };
-        
-        properties.insert(prop_name.clone(), PropertyInfo {
-            name: prop_name.clone(),
-            type_name,
-            is_list,
-            is_nullable,
-            description: prop.description.clone(),
-            reference,
-        });
+    println!("\n{}", style("API changes").bold());
+    println!("  Endpoints: {} added, {} changed, {} removed", counts.0, counts.1, counts.2);
+    for line in &endpoint_lines {
+        println!("{}", line);
     }
     
-    SchemaInfo {
-        name: name.to_string(),
-        properties,
-        required: schema.required.clone(),
-        description: schema.description.clone(),
+    let (added, changed, removed) = changes(&previous.schemas, &current.schemas);
+    println!("  Schemas: {} added, {} changed, {} removed", added.len(), changed.len(), removed.len());
+    for name in added {
+        println!("    + {}", name);
+    }
+    for name in changed {
+        println!("    ~ {}", name);
+    }
+    for name in removed {
+        println!("    - {}", name);
     }
 }
 
-fn determine_property_type(prop: &SwaggerProperty) -> String {
-    if let Some(ref_path) = &prop.reference {
-        // It's a reference to another schema
-        if let Some(model_name) = ref_path.split('/').last() {
-            return model_name.to_string();
+/// Serialization format of a spec document
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpecFormat {
+    Json,
+    Yaml,
+}
+
+impl SpecFormat {
+    /// Format implied by a file name or URL path, if it has a known extension
+    fn from_extension(location: &str) -> Option<Self> {
+        // Ignore any query string or fragment on URLs
+        let path = location.split(['?', '#']).next().unwrap_or(location);
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(SpecFormat::Json),
+            "yaml" | "yml" => Some(SpecFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Format implied by an HTTP Content-Type header, if it names one
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        if mime.ends_with("/json") || mime.ends_with("+json") {
+            Some(SpecFormat::Json)
+        } else if mime.ends_with("/yaml") || mime.ends_with("/x-yaml") || mime.ends_with("+yaml") {
+            Some(SpecFormat::Yaml)
+        } else {
+            None
+        }
+    }
+
+    /// Guess the format from the document itself: JSON documents start with `{` or `[`
+    fn sniff(content: &str) -> Self {
+        match content.trim_start_matches('\u{feff}').trim_start().chars().next() {
+            Some('{') | Some('[') => SpecFormat::Json,
+            _ => SpecFormat::Yaml,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SpecFormat::Json => "JSON",
+            SpecFormat::Yaml => "YAML",
+        }
+    }
+}
+
+/// Parse a spec document, reporting syntax and structure errors with their position
+fn parse_spec<T: DeserializeOwned>(content: &str, format: SpecFormat, origin: &str) -> Result<T> {
+    let parsed = match format {
+        SpecFormat::Json => serde_json::from_str(content).map_err(|e| {
+            (Some((e.line(), e.column())), e.to_string())
+        }),
+        SpecFormat::Yaml => serde_yaml::from_str(content).map_err(|e| {
+            (e.location().map(|l| (l.line(), l.column())), e.to_string())
+        }),
+    };
+
+    parsed.map_err(|(location, message)| match location {
+        Some((line, column)) => anyhow::anyhow!(
+            "Failed to parse {} as {} at line {}, column {}: {}",
+            origin, format.name(), line, column, message
+        ),
+        None => anyhow::anyhow!("Failed to parse {} as {}: {}", origin, format.name(), message),
+    })
+}
+
+/// Where a spec document (the root spec or a file it references) lives
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DocumentLocation {
+    Url(reqwest::Url),
+    File(PathBuf),
+}
+
+impl DocumentLocation {
+    fn from_source(source: &SwaggerSource) -> Result<Self> {
+        match source {
+            SwaggerSource::Url(url) => reqwest::Url::parse(url)
+                .map(DocumentLocation::Url)
+                .with_context(|| format!("Invalid Swagger spec URL: {}", url)),
+            SwaggerSource::File(path) => fs::canonicalize(path)
+                .map(DocumentLocation::File)
+                .with_context(|| format!("Failed to read Swagger file: {:?}", path)),
+        }
+    }
+
+    /// Resolve the document part of a `$ref` relative to this document
+    fn join(&self, location: &str) -> Result<Self> {
+        if let Ok(url) = reqwest::Url::parse(location) {
+            if url.scheme() == "http" || url.scheme() == "https" {
+                return Ok(DocumentLocation::Url(url));
+            }
+        }
+
+        match self {
+            DocumentLocation::Url(base) => base.join(location)
+                .map(DocumentLocation::Url)
+                .with_context(|| format!("Invalid reference '{}' in {}", location, self)),
+            DocumentLocation::File(base) => {
+                let path = base.parent().unwrap_or(Path::new(".")).join(location);
+                fs::canonicalize(&path)
+                    .map(DocumentLocation::File)
+                    .with_context(|| format!("Referenced file {:?} not found (from {})", path, self))
+            }
+        }
+    }
+
+    /// Fetch the raw document and work out its format
+    fn read(&self) -> Result<(String, SpecFormat)> {
+        match self {
+            DocumentLocation::Url(url) => {
+                let response = reqwest::blocking::get(url.clone())
+                    .context(format!("Failed to fetch Swagger spec from {}", url))?;
+                
+                if !response.status().is_success() {
+                    return Err(anyhow::anyhow!(
+                        "Failed to fetch Swagger spec from {}, status code: {}", 
+                        url,
+                        response.status()
+                    ));
+                }
+                
+                let content_type = response.headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string());
+                
+                let body = response.text()
+                    .context(format!("Failed to read Swagger spec from {}", url))?;
+                
+                let format = SpecFormat::from_extension(url.as_str())
+                    .or_else(|| content_type.as_deref().and_then(SpecFormat::from_content_type))
+                    .unwrap_or_else(|| SpecFormat::sniff(&body));
+                
+                Ok((body, format))
+            },
+            DocumentLocation::File(path) => {
+                let file_content = fs::read_to_string(path)
+                    .context(format!("Failed to read Swagger file: {:?}", path))?;
+                
+                let format = SpecFormat::from_extension(&path.to_string_lossy())
+                    .unwrap_or_else(|| SpecFormat::sniff(&file_content));
+                
+                Ok((file_content, format))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DocumentLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentLocation::Url(url) => write!(f, "{}", url),
+            DocumentLocation::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// What the value being walked is, which decides how an external `$ref` in it is bundled
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RefContext {
+    /// A schema: external refs become named schemas in the bundled spec
+    Schema,
+    /// A map whose values are schemas (`properties`, `definitions`, `components/schemas`)
+    SchemaMap,
+    /// A list whose items are schemas (`allOf`, `oneOf`, `anyOf`)
+    SchemaList,
+    /// Anything else (path items, parameters, responses): external refs are inlined
+    Other,
+}
+
+impl RefContext {
+    /// Context of the value stored under `key` in an object with this context
+    fn child(self, key: &str) -> Self {
+        match self {
+            RefContext::SchemaMap => RefContext::Schema,
+            RefContext::Schema => match key {
+                "properties" | "patternProperties" | "definitions" | "$defs" => RefContext::SchemaMap,
+                "items" | "additionalProperties" | "not" => RefContext::Schema,
+                "allOf" | "oneOf" | "anyOf" => RefContext::SchemaList,
+                _ => RefContext::Other,
+            },
+            RefContext::SchemaList | RefContext::Other => match key {
+                "schema" => RefContext::Schema,
+                "schemas" | "definitions" => RefContext::SchemaMap,
+                _ => RefContext::Other,
+            },
+        }
+    }
+
+    /// Context of the items of an array with this context
+    fn item(self) -> Self {
+        match self {
+            RefContext::SchemaList => RefContext::Schema,
+            other => other,
+        }
+    }
+}
+
+/// Bundles a spec split across files into a single document with only local `$ref`s
+///
+/// External schemas are copied into the root's shared schemas (under the name
+/// they had in their own file) and referenced from there, so recursive models
+/// keep working. Other external objects are inlined where they are referenced.
+struct SpecBundler {
+    root: DocumentLocation,
+    /// Parsed documents by location, so every file is read once
+    documents: HashMap<DocumentLocation, serde_json::Value>,
+    /// Bundled schema name for each external (document, pointer) already copied
+    bundled: HashMap<(DocumentLocation, String), String>,
+    /// External schemas to add to the root document
+    schemas: serde_json::Map<String, serde_json::Value>,
+    /// Schema names already in use in the bundled spec
+    names: HashSet<String>,
+    /// External objects currently being inlined, to detect reference cycles
+    inlining: Vec<(DocumentLocation, String)>,
+    /// Local reference prefix for shared schemas in the root's layout
+    schema_prefix: &'static str,
+}
+
+impl SpecBundler {
+    fn new(root: DocumentLocation, document: &serde_json::Value) -> Self {
+        // Swagger 2.0 keeps schemas in `definitions`, OpenAPI 3 in `components/schemas`
+        let schema_prefix = if document.get("swagger").is_some() {
+            SCHEMA_REF_PREFIXES[0]
+        } else {
+            SCHEMA_REF_PREFIXES[1]
+        };
+
+        let names = document
+            .pointer(&schema_prefix[1..schema_prefix.len() - 1])
+            .and_then(|schemas| schemas.as_object())
+            .map(|schemas| schemas.keys().cloned().collect())
+            .unwrap_or_default();
+
+        Self {
+            root,
+            documents: HashMap::new(),
+            bundled: HashMap::new(),
+            schemas: serde_json::Map::new(),
+            names,
+            inlining: Vec::new(),
+            schema_prefix,
+        }
+    }
+
+    /// Resolve every external reference in the root document
+    fn bundle(mut self, mut document: serde_json::Value) -> Result<serde_json::Value> {
+        let root = self.root.clone();
+        let pointer = &self.schema_prefix[1..self.schema_prefix.len() - 1];
+
+        // Shared schemas that only point at another file keep their name in the root
+        if let Some(schemas) = document.pointer_mut(pointer).and_then(|s| s.as_object_mut()) {
+            for (name, schema) in schemas.iter_mut() {
+                let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) else { continue };
+                let (location, target) = reference.split_once('#').unwrap_or((reference, ""));
+                if location.is_empty() {
+                    continue;
+                }
+
+                let document = root.join(location)?;
+                if document == root {
+                    continue;
+                }
+                let key = (document, target.to_string());
+                self.bundled.insert(key.clone(), name.clone());
+                let mut resolved = self.target(&key.0, target)?;
+                self.bundle_value(&mut resolved, &key.0, RefContext::Schema)
+                    .with_context(|| format!("Failed to bundle schema '{}'", name))?;
+                *schema = resolved;
+            }
+        }
+
+        self.bundle_value(&mut document, &root, RefContext::Other)?;
+
+        if !self.schemas.is_empty() {
+            let mut target = &mut document;
+            for key in pointer.split('/').skip(1) {
+                target = target
+                    .as_object_mut()
+                    .ok_or_else(|| anyhow::anyhow!("Expected an object at '{}' in {}", pointer, root))?
+                    .entry(key)
+                    .or_insert_with(|| serde_json::json!({}));
+            }
+
+            let schemas = target
+                .as_object_mut()
+                .ok_or_else(|| anyhow::anyhow!("Expected an object at '{}' in {}", pointer, root))?;
+            schemas.append(&mut self.schemas);
+        }
+
+        Ok(document)
+    }
+
+    fn bundle_value(
+        &mut self,
+        value: &mut serde_json::Value,
+        base: &DocumentLocation,
+        context: RefContext,
+    ) -> Result<()> {
+        if let Some(reference) = value.get("$ref").and_then(|r| r.as_str()).map(str::to_string) {
+            // Local references in the root document are already resolvable
+            if base == &self.root && reference.starts_with('#') {
+                return Ok(());
+            }
+
+            let (location, pointer) = reference.split_once('#').unwrap_or((reference.as_str(), ""));
+            let document = if location.is_empty() { base.clone() } else { base.join(location)? };
+
+            *value = if document == self.root {
+                // A split file pointing back into the root spec
+                serde_json::json!({ "$ref": format!("#{}", pointer) })
+            } else if context == RefContext::Schema {
+                let name = self.bundle_schema(document, pointer)?;
+                serde_json::json!({ "$ref": format!("{}{}", self.schema_prefix, name) })
+            } else {
+                self.inline(document, pointer, context)
+                    .with_context(|| format!("Failed to resolve $ref '{}' in {}", reference, base))?
+            };
+            return Ok(());
+        }
+
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    self.bundle_value(child, base, context.child(key))?;
+                }
+            },
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.bundle_value(item, base, context.item())?;
+                }
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    /// Copy an external schema into the bundled spec, returning its name there
+    fn bundle_schema(&mut self, document: DocumentLocation, pointer: &str) -> Result<String> {
+        let key = (document, pointer.to_string());
+        if let Some(name) = self.bundled.get(&key) {
+            return Ok(name.clone());
+        }
+
+        let base_name = external_schema_name(&key.0, pointer);
+        let mut name = base_name.clone();
+        let mut suffix = 2;
+        while self.names.contains(&name) {
+            name = format!("{}{}", base_name, suffix);
+            suffix += 1;
+        }
+
+        // Register the name before walking the schema so recursive references resolve to it
+        self.names.insert(name.clone());
+        self.bundled.insert(key.clone(), name.clone());
+
+        let mut schema = self.target(&key.0, pointer)?;
+        self.bundle_value(&mut schema, &key.0, RefContext::Schema)
+            .with_context(|| format!("Failed to bundle schema '{}#{}'", key.0, pointer))?;
+        self.schemas.insert(name.clone(), schema);
+
+        Ok(name)
+    }
+
+    /// Resolve an external non-schema object, with its own references bundled
+    fn inline(
+        &mut self,
+        document: DocumentLocation,
+        pointer: &str,
+        context: RefContext,
+    ) -> Result<serde_json::Value> {
+        let key = (document, pointer.to_string());
+        if let Some(start) = self.inlining.iter().position(|entry| entry == &key) {
+            let chain: Vec<String> = self.inlining[start..]
+                .iter()
+                .chain(std::iter::once(&key))
+                .map(|(document, pointer)| format!("{}#{}", document, pointer))
+                .collect();
+            return Err(anyhow::anyhow!("Circular $ref: {}", chain.join(" -> ")));
+        }
+
+        let mut target = self.target(&key.0, pointer)?;
+        self.inlining.push(key.clone());
+        let result = self.bundle_value(&mut target, &key.0, context);
+        self.inlining.pop();
+        result?;
+
+        Ok(target)
+    }
+
+    /// The value a JSON pointer refers to in a (possibly not yet loaded) document
+    fn target(&mut self, document: &DocumentLocation, pointer: &str) -> Result<serde_json::Value> {
+        if !self.documents.contains_key(document) {
+            let (content, format) = document.read()?;
+            let parsed: serde_json::Value = parse_spec(&content, format, &document.to_string())?;
+            self.documents.insert(document.clone(), parsed);
+        }
+
+        self.documents[document]
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("'#{}' not found in {}", pointer, document))
+    }
+}
+
+/// Name for an external schema: the last segment of its pointer, or the file name
+/// when the reference points at a whole document
+fn external_schema_name(document: &DocumentLocation, pointer: &str) -> String {
+    let from_pointer = pointer
+        .rsplit('/')
+        .next()
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"));
+
+    from_pointer.unwrap_or_else(|| {
+        let path = match document {
+            DocumentLocation::Url(url) => url.path().to_string(),
+            DocumentLocation::File(path) => path.to_string_lossy().to_string(),
+        };
+        Path::new(&path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("Model")
+            .to_case(Case::Pascal)
+    })
+}
+
+/// Load Swagger spec from URL or file, bundling any external references into it
+fn load_swagger_spec(source: SwaggerSource) -> Result<SwaggerSpec> {
+    match &source {
+        SwaggerSource::Url(_) => println!("Fetching Swagger specification from URL..."),
+        SwaggerSource::File(_) => println!("Reading Swagger specification from file..."),
+    }
+    
+    read_swagger_spec(&source, true)
+}
+
+/// Read and bundle a spec, optionally reporting progress on stdout
+fn read_swagger_spec(source: &SwaggerSource, verbose: bool) -> Result<SwaggerSpec> {
+    let root = DocumentLocation::from_source(source)?;
+    let (content, format) = root.read()?;
+    let origin = match source {
+        SwaggerSource::Url(url) => url.clone(),
+        SwaggerSource::File(path) => path.display().to_string(),
+    };
+    
+    // Parse the root into the spec model first so structural errors point at a line
+    let spec: SwaggerSpec = parse_spec(&content, format, &origin)?;
+    let document: serde_json::Value = parse_spec(&content, format, &origin)?;
+    if !has_external_refs(&document) {
+        return Ok(spec);
+    }
+    
+    if verbose {
+        println!("Resolving references to other spec files...");
+    }
+    let bundled = SpecBundler::new(root, &document).bundle(document)?;
+    serde_json::from_value(bundled)
+        .with_context(|| format!("Failed to read bundled spec from {}", origin))
+}
+
+/// Whether a document contains any `$ref` that points outside of itself
+fn has_external_refs(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map.iter().any(|(key, child)| {
+            (key == "$ref" && child.as_str().is_some_and(|r| !r.starts_with('#')))
+                || has_external_refs(child)
+        }),
+        serde_json::Value::Array(items) => items.iter().any(has_external_refs),
+        _ => false,
+    }
+}
+
+/// Extract all domains/tags from the Swagger spec
+/// A domain is represented by a tag in the Swagger spec
+struct Domain {
+    name: String,
+    #[allow(dead_code)]
+    description: String,
+    endpoints: Vec<Endpoint>,
+}
+
+#[derive(Clone)]
+struct Endpoint {
+    path: String,
+    method: String,
+    operation_id: String,
+    /// Unique name of the endpoint's Dart methods within its domain
+    method_name: String,
+    summary: String,
+    parameters: Vec<SwaggerParameter>,
+    response_type: Option<ResponseType>,
+}
+
+/// Schema a successful response returns, on its own or as a list
+#[derive(Debug, Clone, PartialEq)]
+struct ResponseType {
+    schema: String,
+    is_list: bool,
+}
+
+impl ResponseType {
+    /// Dart type of the response, naming the schema with `name`
+    fn dart_type(&self, name: impl Fn(&str) -> String) -> String {
+        if self.is_list {
+            format!("List<{}>", name(&self.schema))
+        } else {
+            name(&self.schema)
+        }
+    }
+}
+
+impl Endpoint {
+    /// Endpoint for one operation of a path, with its parameters and response type resolved
+    fn new(
+        spec: &SwaggerSpec,
+        path: &str,
+        path_item: &SwaggerPathItem,
+        method: &str,
+        operation: &SwaggerOperation,
+    ) -> Result<Self> {
+        let context = || format!("{} {}", method.to_uppercase(), path);
+        Ok(Endpoint {
+            path: path.to_string(),
+            method: method.to_uppercase(),
+            operation_id: operation.operation_id.clone(),
+            method_name: String::new(),
+            summary: operation.summary.clone(),
+            parameters: spec.operation_parameters(path_item, operation).with_context(context)?,
+            response_type: extract_response_type(spec, &operation.responses).with_context(context)?,
+        })
+    }
+
+    /// Response type as written in signatures, `void` without one
+    fn response_signature(&self) -> String {
+        self.response_type.as_ref().map_or_else(|| "void".to_string(), |response| response.dart_type(str::to_string))
+    }
+}
+
+/// How operations are grouped into domains, and so into features
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupingStrategy {
+    /// By tag; untagged operations fall back to their path prefix
+    #[default]
+    Tag,
+    /// By the first segment of the path
+    PathPrefix,
+    /// By the part of the operationId before the first `_`, `.`, `-` or `/`
+    OperationId,
+    /// Everything in one `api` feature
+    Single,
+}
+
+/// Domain of operations with no usable tag or operationId prefix, and of `Single` grouping
+const DEFAULT_DOMAIN: &str = "api";
+
+/// First literal segment of a path, e.g. `users` for `/users/{id}/posts`
+fn path_prefix(path: &str) -> String {
+    path.split('/')
+        .find(|segment| !segment.is_empty() && !segment.starts_with('{'))
+        .unwrap_or(DEFAULT_DOMAIN)
+        .to_string()
+}
+
+/// Prefix of an operationId such as `users_list` or `users.get`
+fn operation_id_prefix(operation_id: &str) -> Option<String> {
+    let (prefix, rest) = operation_id.split_once(['_', '.', '-', '/'])?;
+    (!prefix.is_empty() && !rest.is_empty()).then(|| prefix.to_string())
+}
+
+/// Group the spec's operations into domains
+///
+/// Operations that cannot be grouped the requested way (no tags, or an
+/// operationId without a prefix) are grouped by path prefix, with a warning.
+fn extract_domains(spec: &SwaggerSpec, grouping: GroupingStrategy) -> Result<Vec<Domain>> {
+    // First, collect all tags as domains
+    let tags = if grouping == GroupingStrategy::Tag { spec.tags.as_slice() } else { &[] };
+    let mut domains: IndexMap<String, Domain> = tags.iter()
+        .map(|tag| {
+            (tag.name.clone(), Domain {
+                name: tag.name.clone(),
+                description: tag.description.clone(),
+                endpoints: Vec::new(),
+            })
+        })
+        .collect();
+    
+    // Then associate each path/operation with its domains
+    let mut regrouped = Vec::new();
+    for (path, path_item) in &spec.paths {
+        for (method, operation) in path_item.operations() {
+            let domain_names = match grouping {
+                GroupingStrategy::Tag if !operation.tags.is_empty() => operation.tags.clone(),
+                GroupingStrategy::OperationId => match operation_id_prefix(&operation.operation_id) {
+                    Some(prefix) => vec![prefix],
+                    None => Vec::new(),
+                },
+                GroupingStrategy::PathPrefix => vec![path_prefix(path)],
+                GroupingStrategy::Single => vec![DEFAULT_DOMAIN.to_string()],
+                GroupingStrategy::Tag => Vec::new(),
+            };
+            
+            // Fall back to the path prefix, and report it
+            let domain_names = if domain_names.is_empty() {
+                let prefix = path_prefix(path);
+                regrouped.push(format!("{} {} → {}", method.to_uppercase(), path, prefix));
+                vec![prefix]
+            } else {
+                domain_names
+            };
+            
+            // Create endpoint
+            let endpoint = Endpoint::new(spec, path, path_item, method, operation)?;
+            
+            // Add to each associated domain
+            for name in domain_names {
+                if let Some(domain) = domains.get_mut(&name) {
+                    domain.endpoints.push(endpoint.clone());
+                } else {
+                    // Create a new domain if the tag wasn't defined in the tags section
+                    domains.insert(name.clone(), Domain {
+                        name,
+                        description: String::new(),
+                        endpoints: vec![endpoint.clone()],
+                    });
+                }
+            }
+        }
+    }
+    
+    if !regrouped.is_empty() {
+        let reason = match grouping {
+            GroupingStrategy::OperationId => "have no operationId prefix",
+            _ => "have no tags",
+        };
+        println!(
+            "{} {} operations {} and were grouped by path prefix:",
+            style("⚠️").yellow().bold(),
+            regrouped.len(),
+            reason
+        );
+        for operation in &regrouped {
+            println!("  {}", operation);
+        }
+    }
+    
+    // Name the Dart methods, reporting names that had to be invented
+    let mut synthesized = Vec::new();
+    for domain in domains.values_mut() {
+        assign_method_names(domain, &mut synthesized);
+    }
+    if !synthesized.is_empty() {
+        println!(
+            "{} {} operations have no usable operationId and were given method names:",
+            style("⚠️").yellow().bold(),
+            synthesized.len()
+        );
+        for name in &synthesized {
+            println!("  {}", name);
+        }
+    }
+    
+    Ok(domains.into_values().collect())
+}
+
+/// Dart method name for an operation without a usable operationId, from its
+/// method and path: `GET /users/{id}` becomes `getUserById`
+fn synthesize_method_name(method: &str, path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let mut words = vec![method.to_lowercase()];
+    
+    for (i, segment) in segments.iter().enumerate() {
+        if let Some(param) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            words.push(format!("By {}", param));
+        } else if segments.get(i + 1).is_some_and(|next| next.starts_with('{')) {
+            // A collection followed by an id names a single item
+            words.push(singular(segment));
+        } else {
+            words.push(segment.to_string());
+        }
+    }
+    
+    dart_identifier(&words.join(" "))
+}
+
+/// Plurals the suffix rules of `singular` get wrong
+const IRREGULAR_PLURALS: [(&str, &str); 8] = [
+    ("cookies", "cookie"),
+    ("movies", "movie"),
+    ("heroes", "hero"),
+    ("potatoes", "potato"),
+    ("statuses", "status"),
+    ("buses", "bus"),
+    ("people", "person"),
+    ("children", "child"),
+];
+
+/// Naive English singular of a path segment such as `users` or `categories`
+fn singular(word: &str) -> String {
+    if let Some((_, singular)) = IRREGULAR_PLURALS.iter().find(|(plural, _)| *plural == word) {
+        singular.to_string()
+    } else if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if word.ends_with("ss") || word.ends_with("us") {
+        word.to_string()
+    } else if let Some(stem) = word.strip_suffix("sses").or_else(|| word.strip_suffix("xes")) {
+        format!("{}{}", stem, if word.ends_with("sses") { "ss" } else { "x" })
+    } else {
+        word.strip_suffix('s').unwrap_or(word).to_string()
+    }
+}
+
+/// Give every endpoint of a domain a unique Dart method name
+///
+/// The operationId is used when it has one; otherwise, or when it clashes with
+/// an earlier endpoint, a name is synthesized from the method and path (with a
+/// numeric suffix if that is taken too). Invented names are added to `report`.
+fn assign_method_names(domain: &mut Domain, report: &mut Vec<String>) {
+    let mut used = HashSet::new();
+    
+    for endpoint in &mut domain.endpoints {
+        let has_operation_id = endpoint.operation_id.chars().any(|c| c.is_ascii_alphanumeric());
+        let preferred = has_operation_id.then(|| dart_identifier(&endpoint.operation_id));
+        
+        let (name, reason) = match preferred {
+            Some(name) if !used.contains(&name) => (name, None),
+            Some(_) => {
+                let reason = format!("duplicate operationId '{}'", endpoint.operation_id);
+                (synthesize_method_name(&endpoint.method, &endpoint.path), Some(reason))
+            },
+            None => (synthesize_method_name(&endpoint.method, &endpoint.path), Some("no operationId".to_string())),
+        };
+        
+        let mut unique = name.clone();
+        let mut suffix = 2;
+        while used.contains(&unique) {
+            unique = format!("{}{}", name, suffix);
+            suffix += 1;
+        }
+        
+        if let Some(reason) = reason {
+            report.push(format!("{}: {} {} → {} ({})", domain.name, endpoint.method, endpoint.path, unique, reason));
+        }
+        used.insert(unique.clone());
+        endpoint.method_name = unique;
+    }
+}
+
+/// Extract response type from operation responses
+fn extract_response_type(spec: &SwaggerSpec, responses: &IndexMap<String, SwaggerResponse>) -> Result<Option<ResponseType>> {
+    // Look for the first documented success response
+    for status in ["200", "201", "202", "203", "206", "2XX", "default"] {
+        if let Some(response) = responses.get(status) {
+            let response = spec.resolve_response(response)?;
+            
+            // Swagger 2.0 puts the schema on the response, OpenAPI 3 under content
+            let schema = response.schema.as_ref().or_else(|| json_schema(&response.content));
+            if let Some(schema) = schema {
+                return response_schema_type(spec, schema);
+            }
+            
+            // Only fall through to "default" when there was no success response at all
+            if status != "default" {
+                return Ok(None);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The model a response schema returns: a referenced schema, or a list of one
+/// given inline or through a referenced array schema
+fn response_schema_type(spec: &SwaggerSpec, schema: &SwaggerSchema) -> Result<Option<ResponseType>> {
+    let list_of = |items: Option<&SwaggerSchema>| {
+        items
+            .and_then(|items| items.reference.as_deref())
+            .and_then(schema_ref_name)
+            .map(|schema| ResponseType { schema, is_list: true })
+    };
+
+    match &schema.reference {
+        Some(reference) => {
+            // Array schemas are not generated as models, so a named one returns a list of its items
+            if let Ok(resolved) = spec.resolve_schema(reference) {
+                if resolved.r#type == "array" {
+                    return Ok(list_of(resolved.items.as_deref()));
+                }
+            }
+            Ok(schema_ref_name(reference).map(|schema| ResponseType { schema, is_list: false }))
+        },
+        None if schema.r#type == "array" => Ok(list_of(schema.items.as_deref())),
+        None => Ok(None),
+    }
+}
+
+/// Extract schemas for model generation
+struct SchemaInfo {
+    name: String,
+    properties: IndexMap<String, PropertyInfo>,
+    required: Vec<String>,
+    description: String,
+    /// Property whose value selects the variant of a polymorphic (`oneOf`/`anyOf`) schema
+    discriminator: Option<String>,
+    variants: Vec<VariantInfo>,
+    /// Enums declared by this schema: the schema itself, or its inline property enums
+    enums: Vec<EnumInfo>,
+}
+
+impl SchemaInfo {
+    /// Whether the schema is a named enum rather than an object
+    fn is_enum(&self) -> bool {
+        self.enums.iter().any(|e| e.name == self.name)
+    }
+
+    fn has_date_time_field(&self) -> bool {
+        self.properties.values().any(|prop| prop.type_name.contains("DateTime"))
+    }
+
+    fn has_converted_field(&self) -> bool {
+        self.properties.values().any(|prop| prop.converter.is_some())
+    }
+
+    fn has_binary_field(&self) -> bool {
+        self.properties.values().any(|prop| prop.type_name.contains("Uint8List"))
+    }
+
+    fn has_list_field(&self) -> bool {
+        self.properties.values().any(|prop| prop.is_list)
+    }
+}
+
+#[allow(dead_code)]
+struct PropertyInfo {
+    name: String,
+    type_name: String,
+    is_list: bool,
+    is_nullable: bool,
+    description: String,
+    reference: Option<String>,
+    /// Dart enum used by the property, itself or as its list item type
+    enum_name: Option<String>,
+    /// `JsonConverter` needed to (de)serialize the property, from `json_converters.dart`
+    converter: Option<&'static str>,
+}
+
+/// A string or integer enum generated as a Dart enum
+struct EnumInfo {
+    name: String,
+    description: String,
+    values: Vec<serde_json::Value>,
+}
+
+impl EnumInfo {
+    fn new(name: &str, description: &str, values: &[serde_json::Value]) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            // A `null` entry only marks the field as nullable
+            values: values.iter().filter(|v| !v.is_null()).cloned().collect(),
+        }
+    }
+}
+
+/// One alternative of a polymorphic schema
+struct VariantInfo {
+    /// Discriminator value identifying the variant
+    value: String,
+    /// Name of the variant's schema
+    schema: String,
+}
+
+fn extract_schemas(spec: &SwaggerSpec, type_mappings: &TypeMappings) -> Result<IndexMap<String, SchemaInfo>> {
+    let mut result = IndexMap::new();
+    
+    // OpenAPI 3 schemas are under components/schemas, Swagger 2.0 ones under definitions
+    for (name, schema) in spec.all_schemas() {
+        let schema_info = extract_schema_info(spec, name, schema, type_mappings)
+            .with_context(|| format!("Failed to read schema '{}'", name))?;
+        result.insert(name.clone(), schema_info);
+    }
+    
+    Ok(result)
+}
+
+fn extract_schema_info(
+    spec: &SwaggerSpec,
+    name: &str,
+    schema: &SwaggerSchema,
+    type_mappings: &TypeMappings,
+) -> Result<SchemaInfo> {
+    // Named enum schemas have no properties of their own
+    if !schema.enum_values.is_empty() {
+        return Ok(SchemaInfo {
+            name: name.to_string(),
+            properties: IndexMap::new(),
+            required: Vec::new(),
+            description: schema.description.clone(),
+            discriminator: None,
+            variants: Vec::new(),
+            enums: vec![EnumInfo::new(name, &schema.description, &schema.enum_values)],
+        });
+    }
+    
+    let mut declared = IndexMap::new();
+    let mut required = Vec::new();
+    let mut visited = HashSet::from([name.to_string()]);
+    collect_properties(spec, schema, &mut declared, &mut required, &mut visited)?;
+    
+    let mut discriminator = None;
+    let mut variants = Vec::new();
+    
+    let alternatives = if schema.one_of.is_empty() { &schema.any_of } else { &schema.one_of };
+    if !alternatives.is_empty() {
+        let variant_names: Option<Vec<String>> = alternatives
+            .iter()
+            .map(|alternative| alternative.reference.as_deref().and_then(schema_ref_name))
+            .collect();
+        
+        match (&schema.discriminator, variant_names) {
+            (Some(property), Some(names)) => {
+                discriminator = Some(property.property_name().to_string());
+                variants = names
+                    .into_iter()
+                    .map(|variant| VariantInfo { value: property.value_for(&variant), schema: variant })
+                    .collect();
+            },
+            _ => {
+                // Without a discriminator the alternatives cannot be told apart,
+                // so accept the union of their properties, all optional
+                for alternative in alternatives {
+                    let mut ignored_required = Vec::new();
+                    collect_properties(spec, alternative, &mut declared, &mut ignored_required, &mut visited.clone())?;
+                }
+            },
+        }
+    }
+    
+    let mut properties = IndexMap::new();
+    let mut enums = Vec::new();
+    
+    for (prop_name, prop) in declared {
+        let mut type_name = determine_property_type(prop, type_mappings);
+        let is_list = prop.r#type == "array";
+        let is_nullable = !required.contains(&prop_name);
+        
+        // Inline enums get a Dart enum named after the schema and property
+        let inline_enum = match &prop.items {
+            Some(items) if is_list => &items.enum_values,
+            _ => &prop.enum_values,
+        };
+        let mut enum_name = None;
+        if !inline_enum.is_empty() {
+            let name = format!("{}{}", name, prop_name.to_case(Case::Pascal));
+            type_name = if is_list { format!("List<{}>", name) } else { name.clone() };
+            enums.push(EnumInfo::new(&name, &prop.description, inline_enum));
+            enum_name = Some(name);
+        }
+        
+        let reference = if let Some(ref_path) = property_reference(prop) {
+            // Extract model name from reference (e.g., "#/definitions/User" -> "User")
+            schema_ref_name(ref_path)
+        } else if let Some(items) = &prop.items {
+            // For array items with a reference
+            items.reference.as_deref().and_then(schema_ref_name)
+        } else {
+            None
+        };
+        
+        let converter = json_converter(&type_name, prop);
+        properties.insert(prop_name.clone(), PropertyInfo {
+            name: prop_name,
+            type_name,
+            is_list,
+            is_nullable,
+            description: prop.description.clone(),
+            converter,
+            reference: reference.clone(),
+            enum_name: enum_name.or_else(|| referenced_enum(spec, reference.as_deref())),
+        });
+    }
+    
+    Ok(SchemaInfo {
+        name: name.to_string(),
+        properties,
+        required,
+        description: schema.description.clone(),
+        discriminator,
+        variants,
+        enums,
+    })
+}
+
+/// Name of a referenced schema when it is a named enum
+fn referenced_enum(spec: &SwaggerSpec, reference: Option<&str>) -> Option<String> {
+    let name = reference?;
+    spec.all_schemas()
+        .find(|(schema_name, schema)| *schema_name == name && !schema.enum_values.is_empty())
+        .map(|(schema_name, _)| schema_name.clone())
+}
+
+/// Add the properties of a schema to a property set, flattening `allOf` and
+/// following references to other schemas
+fn collect_properties<'a>(
+    spec: &'a SwaggerSpec,
+    schema: &'a SwaggerSchema,
+    properties: &mut IndexMap<String, &'a SwaggerProperty>,
+    required: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+) -> Result<()> {
+    if let Some(reference) = &schema.reference {
+        // Guard against schemas that (indirectly) inherit from themselves
+        if !visited.insert(schema_ref_name(reference).unwrap_or_else(|| reference.clone())) {
+            return Ok(());
+        }
+        return collect_properties(spec, spec.resolve_schema(reference)?, properties, required, visited);
+    }
+    
+    for part in &schema.all_of {
+        collect_properties(spec, part, properties, required, visited)?;
+    }
+    
+    for (prop_name, prop) in &schema.properties {
+        properties.insert(prop_name.clone(), prop);
+    }
+    
+    for name in &schema.required {
+        if !required.contains(name) {
+            required.push(name.clone());
+        }
+    }
+    
+    Ok(())
+}
+
+/// Schema a property refers to, directly or through a single-entry `allOf`
+fn property_reference(prop: &SwaggerProperty) -> Option<&String> {
+    let wrapped = match prop.all_of.as_slice() {
+        [only] => only.reference.as_ref(),
+        _ => None,
+    };
+    prop.reference.as_ref().or(wrapped)
+}
+
+fn determine_property_type(prop: &SwaggerProperty, type_mappings: &TypeMappings) -> String {
+    if let Some(ref_path) = property_reference(prop) {
+        // It's a reference to another schema
+        if let Some(model_name) = schema_ref_name(ref_path) {
+            return model_name;
         }
     }
     
@@ -395,134 +1811,694 @@ fn determine_property_type(prop: &SwaggerProperty) -> String {
         if let Some(items) = &prop.items {
             if let Some(ref_path) = &items.reference {
                 // Array of references
-                if let Some(model_name) = ref_path.split('/').last() {
+                if let Some(model_name) = schema_ref_name(ref_path) {
                     return format!("List<{}>", model_name);
                 }
             }
-            // Array of primitive type
-            return format!("List<{}>", map_type(&items.r#type));
+            // Array of primitive type
+            return format!("List<{}>", type_mappings.dart_type(&items.r#type, items.format.as_deref()));
+        }
+        return "List<dynamic>".to_string();
+    }
+    
+    // Map Swagger/OpenAPI types to Dart types
+    type_mappings.dart_type(&prop.r#type, prop.format.as_deref())
+}
+
+/// Converter a model field needs for types JSON has no native form for
+fn json_converter(type_name: &str, prop: &SwaggerProperty) -> Option<&'static str> {
+    let format = prop.format.as_deref()
+        .or_else(|| prop.items.as_ref().and_then(|items| items.format.as_deref()));
+
+    if type_name.contains("DateTime") && format == Some("date") {
+        Some("DateConverter")
+    } else if type_name.contains("Uint8List") {
+        Some("Base64Converter")
+    } else {
+        None
+    }
+}
+
+/// Dart types for Swagger/OpenAPI `type` and `format` pairs
+///
+/// Keys are a type (`integer`) or a type and format (`string:date-time`), and the
+/// more specific key wins. Projects override entries with `api.type_mappings` in
+/// `flutter_lazy.yaml`.
+pub struct TypeMappings {
+    table: HashMap<String, String>,
+}
+
+impl TypeMappings {
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        let mut table: HashMap<String, String> = [
+            ("integer", "int"),
+            ("integer:int32", "int"),
+            ("integer:int64", "int"),
+            ("number", "double"),
+            ("number:float", "double"),
+            ("number:double", "double"),
+            ("boolean", "bool"),
+            ("string", "String"),
+            ("string:date-time", "DateTime"),
+            ("string:date", "DateTime"),
+            ("string:uuid", "String"),
+            ("string:byte", "Uint8List"),
+            ("string:binary", "Uint8List"),
+            // Swagger 2.0 form file uploads
+            ("file", "MultipartFile"),
+            ("object", "Map<String, dynamic>"),
+        ]
+        .into_iter()
+        .map(|(key, dart_type)| (key.to_string(), dart_type.to_string()))
+        .collect();
+
+        table.extend(overrides.iter().map(|(key, dart_type)| (key.clone(), dart_type.clone())));
+        Self { table }
+    }
+
+    fn dart_type(&self, swagger_type: &str, format: Option<&str>) -> String {
+        format
+            .and_then(|format| self.table.get(&format!("{}:{}", swagger_type, format)))
+            .or_else(|| self.table.get(swagger_type))
+            .cloned()
+            .unwrap_or_else(|| "dynamic".to_string())
+    }
+}
+
+impl Default for TypeMappings {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+/// Dart type of an operation parameter; referenced schemas map to their model class
+fn parameter_type(param: &SwaggerParameter, type_mappings: &TypeMappings) -> String {
+    let dart_type = match &param.schema {
+        // OpenAPI 3 parameters and Swagger 2.0 body parameters
+        Some(schema) => schema_dart_type(schema, type_mappings),
+        // Other Swagger 2.0 parameters declare their type inline
+        None if param.r#type == "array" => {
+            let item_type = param.items.as_deref().map(|items| schema_dart_type(items, type_mappings));
+            format!("List<{}>", item_type.as_deref().unwrap_or("dynamic"))
+        },
+        None => type_mappings.dart_type(&param.r#type, param.format.as_deref()),
+    };
+
+    // Files are uploaded as multipart form fields
+    if param.r#in == "formData" && dart_type == "Uint8List" {
+        "MultipartFile".to_string()
+    } else {
+        dart_type
+    }
+}
+
+fn schema_dart_type(schema: &SwaggerSchema, type_mappings: &TypeMappings) -> String {
+    if let Some(model_name) = schema.reference.as_deref().and_then(schema_ref_name) {
+        return format!("{}Model", model_name);
+    }
+
+    if schema.r#type == "array" {
+        let item_type = schema.items.as_deref().map(|items| schema_dart_type(items, type_mappings));
+        return format!("List<{}>", item_type.as_deref().unwrap_or("dynamic"));
+    }
+
+    type_mappings.dart_type(&schema.r#type, schema.format.as_deref())
+}
+
+/// Words that cannot be used as Dart identifiers
+const DART_RESERVED_WORDS: [&str; 33] = [
+    "assert", "break", "case", "catch", "class", "const", "continue", "default",
+    "do", "else", "enum", "extends", "false", "final", "finally", "for", "if",
+    "in", "is", "new", "null", "rethrow", "return", "super", "switch", "this",
+    "throw", "true", "try", "var", "void", "while", "with",
+];
+
+/// Turn an API name into a camelCase Dart identifier (e.g. "pet-id" -> "petId")
+fn dart_identifier(name: &str) -> String {
+    let words: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect();
+    let identifier = words.to_case(Case::Camel);
+
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("value{}", identifier)
+    } else if DART_RESERVED_WORDS.contains(&identifier.as_str()) {
+        format!("{}Value", identifier)
+    } else {
+        identifier
+    }
+}
+
+/// An endpoint parameter as it appears in generated Dart methods
+struct DartParameter<'a> {
+    param: &'a SwaggerParameter,
+    name: String,
+    dart_type: String,
+}
+
+impl DartParameter<'_> {
+    /// Path parameters are always required, whatever the spec says
+    fn is_required(&self) -> bool {
+        self.param.required || self.param.r#in == "path"
+    }
+
+    fn declaration(&self) -> String {
+        if self.is_required() {
+            format!("required {} {}", self.dart_type, self.name)
+        } else if self.dart_type == "dynamic" {
+            format!("dynamic {}", self.name)
+        } else {
+            format!("{}? {}", self.dart_type, self.name)
+        }
+    }
+
+    /// Map entry for query parameters and headers, skipping unset optional values
+    fn map_entry(&self) -> String {
+        if self.is_required() {
+            format!("'{}': {}", self.param.name, self.name)
+        } else {
+            format!("if ({} != null) '{}': {}", self.name, self.param.name, self.name)
+        }
+    }
+
+    /// Expression sending this parameter as the request body
+    fn body_expression(&self) -> String {
+        let access = if self.is_required() { "." } else { "?." };
+        let is_model = |t: &str| t.ends_with("Model");
+
+        if is_model(&self.dart_type) {
+            format!("{}{}toJson()", self.name, access)
+        } else if let Some(item_type) = self.dart_type.strip_prefix("List<").and_then(|t| t.strip_suffix('>')) {
+            if is_model(item_type) {
+                format!("{}{}map((e) => e.toJson()).toList()", self.name, access)
+            } else {
+                self.name.clone()
+            }
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// Parameters of an endpoint passed to generated methods, with unique Dart names
+fn dart_parameters<'a>(endpoint: &'a Endpoint, type_mappings: &TypeMappings) -> Vec<DartParameter<'a>> {
+    let mut result: Vec<DartParameter> = Vec::new();
+
+    for param in &endpoint.parameters {
+        if !matches!(param.r#in.as_str(), "path" | "query" | "header" | "body" | "formData") {
+            continue;
+        }
+
+        // The same name may be used in different locations (e.g. an `id` path and query parameter)
+        let mut name = dart_identifier(&param.name);
+        if result.iter().any(|p| p.name == name) {
+            name = format!("{}{}", name, param.r#in.to_case(Case::Pascal));
+        }
+
+        result.push(DartParameter {
+            param,
+            name,
+            dart_type: parameter_type(param, type_mappings),
+        });
+    }
+
+    result
+}
+
+/// Named parameter list for a generated method signature
+fn dart_parameter_list(params: &[DartParameter]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let declarations: Vec<String> = params.iter().map(|p| p.declaration()).collect();
+    format!("{{{}}}", declarations.join(", "))
+}
+
+/// Dart string literal for an endpoint path, interpolating path parameters
+/// (e.g. "/pets/{petId}" -> '/pets/$petId')
+fn dart_path(path: &str, params: &[DartParameter]) -> String {
+    let mut result = String::from("'");
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else { break };
+        push_dart_literal(&mut result, &rest[..start]);
+
+        let name = &rest[start + 1..end];
+        let identifier = params
+            .iter()
+            .find(|p| p.param.r#in == "path" && p.param.name == name)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| dart_identifier(name));
+
+        // Braces are needed when the identifier is directly followed by identifier characters
+        rest = &rest[end + 1..];
+        if rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+            result.push_str(&format!("${{{}}}", identifier));
+        } else {
+            result.push_str(&format!("${}", identifier));
+        }
+    }
+
+    push_dart_literal(&mut result, rest);
+    result.push('\'');
+    result
+}
+
+/// Quoted Dart string literal for a value from the spec
+fn dart_string_literal(text: &str) -> String {
+    let mut result = String::from("'");
+    push_dart_literal(&mut result, text);
+    result.push('\'');
+    result
+}
+
+fn push_dart_literal(result: &mut String, text: &str) {
+    for c in text.chars() {
+        if matches!(c, '\'' | '$' | '\\') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+}
+
+/// Where the generated model and entity classes of a schema live
+#[derive(Debug, Clone, PartialEq)]
+enum ModelGroup {
+    /// Used by a single domain: generated with that domain's feature
+    Feature(String),
+    /// Used by several domains: generated once under `lib/core`
+    Shared,
+}
+
+impl ModelGroup {
+    /// Directory of the group's models, relative to the project root
+    fn models_dir(&self) -> PathBuf {
+        match self {
+            ModelGroup::Feature(feature_name) => Path::new("lib/features").join(feature_name).join("data/models"),
+            ModelGroup::Shared => PathBuf::from("lib/core/models"),
+        }
+    }
+
+    /// Directory of the group's entities, relative to the project root
+    fn entities_dir(&self) -> PathBuf {
+        match self {
+            ModelGroup::Feature(feature_name) => Path::new("lib/core/entities").join(feature_name),
+            ModelGroup::Shared => PathBuf::from("lib/core/entities/shared"),
+        }
+    }
+
+    fn enums_file(&self) -> PathBuf {
+        self.models_dir().join("enums.dart")
+    }
+}
+
+/// Group of every schema the generated domains use, for placing files and writing imports
+struct ModelLayout<'a> {
+    schemas: &'a IndexMap<String, SchemaInfo>,
+    groups: HashMap<String, ModelGroup>,
+    /// Schemas to generate, when only some domains are being generated
+    selected: Option<BTreeSet<String>>,
+}
+
+impl<'a> ModelLayout<'a> {
+    /// Place each schema with the only domain using it, or in the shared group
+    fn new(schemas: &'a IndexMap<String, SchemaInfo>, domains: &[Domain]) -> Self {
+        let mut groups = HashMap::new();
+        
+        for domain in domains {
+            let group = ModelGroup::Feature(domain.name.to_case(Case::Snake));
+            for name in domain_schema_closure(domain, schemas) {
+                match groups.get(&name) {
+                    None => {
+                        groups.insert(name, group.clone());
+                    },
+                    Some(existing) if *existing != group => {
+                        groups.insert(name, ModelGroup::Shared);
+                    },
+                    _ => {},
+                }
+            }
         }
-        return "List<dynamic>".to_string();
+        
+        Self { schemas, groups, selected: None }
+    }
+
+    /// Only generate the schemas some of the domains use, keeping every schema's placement
+    fn select(mut self, domains: &[&Domain]) -> Self {
+        let selected = domains.iter()
+            .flat_map(|domain| domain_schema_closure(domain, self.schemas))
+            .collect();
+        self.selected = Some(selected);
+        self
+    }
+
+    fn group(&self, name: &str) -> ModelGroup {
+        self.groups.get(name).cloned().unwrap_or(ModelGroup::Shared)
+    }
+
+    /// Schemas generated in a group, in name order
+    fn members(&self, group: &ModelGroup) -> Vec<&'a SchemaInfo> {
+        let mut names: Vec<&String> = self.groups.iter()
+            .filter(|(_, member_group)| *member_group == group)
+            .map(|(name, _)| name)
+            .filter(|name| self.selected.as_ref().is_none_or(|selected| selected.contains(*name)))
+            .collect();
+        names.sort();
+        names.into_iter().filter_map(|name| self.schemas.get(name)).collect()
+    }
+
+    /// Whether a referenced schema is generated as a model class rather than an enum
+    fn is_model(&self, name: &str) -> bool {
+        self.schemas.get(name).is_some_and(|schema| !schema.is_enum())
+    }
+
+    fn model_file(&self, name: &str) -> PathBuf {
+        self.group(name).models_dir().join(format!("{}_model.dart", name.to_case(Case::Snake)))
+    }
+
+    fn entity_file(&self, name: &str) -> PathBuf {
+        self.group(name).entities_dir().join(format!("{}.dart", entity_name(name).to_case(Case::Snake)))
+    }
+
+    /// Enums file declaring the enum a property uses
+    fn enums_file(&self, schema: &SchemaInfo, prop: &PropertyInfo) -> Option<PathBuf> {
+        let enum_name = prop.enum_name.as_ref()?;
+        
+        // Inline enums are declared with the schema owning them
+        let owner = if self.schemas.contains_key(enum_name) { enum_name } else { &schema.name };
+        Some(self.group(owner).enums_file())
+    }
+
+    /// Referenced schema of a property when it is generated as a model class
+    fn model_reference<'p>(&self, prop: &'p PropertyInfo) -> Option<&'p str> {
+        prop.reference.as_deref().filter(|name| self.is_model(name))
+    }
+
+    /// Type of a property in an entity class: other models are used by their entity class
+    fn entity_type(&self, prop: &PropertyInfo) -> String {
+        match self.model_reference(prop) {
+            Some(name) if prop.is_list => format!("List<{}>", entity_name(name)),
+            Some(name) => entity_name(name),
+            None => prop.type_name.clone(),
+        }
+    }
+
+    /// Type of a property in a model class: other models are used by their model class
+    fn model_type(&self, prop: &PropertyInfo) -> String {
+        match self.model_reference(prop) {
+            Some(name) if prop.is_list => format!("List<{}Model>", name),
+            Some(name) => format!("{}Model", name),
+            None => prop.type_name.clone(),
+        }
+    }
+}
+
+/// Entity class name for a schema
+fn entity_name(schema_name: &str) -> String {
+    if schema_name.ends_with("Model") {
+        schema_name.replace("Model", "")
+    } else {
+        schema_name.to_string()
+    }
+}
+
+/// Name of the schema a parameter or body schema refers to, directly or as its array items
+fn referenced_schema_name(schema: &SwaggerSchema) -> Option<String> {
+    match &schema.reference {
+        Some(reference) => schema_ref_name(reference),
+        None => schema.items.as_deref().and_then(referenced_schema_name),
     }
-    
-    // Map Swagger/OpenAPI types to Dart types
-    map_type(&prop.r#type)
 }
 
-fn map_type(swagger_type: &str) -> String {
-    match swagger_type {
-        "integer" => "int".to_string(),
-        "number" => "double".to_string(),
-        "boolean" => "bool".to_string(),
-        "string" => "String".to_string(),
-        "object" => "Map<String, dynamic>".to_string(),
-        "" => "dynamic".to_string(),
-        _ => "dynamic".to_string(),
+/// Every schema a domain uses: its responses and request bodies, and everything
+/// those reach through properties, array items and polymorphic variants
+fn domain_schema_closure(domain: &Domain, schemas: &IndexMap<String, SchemaInfo>) -> BTreeSet<String> {
+    let mut pending: Vec<String> = Vec::new();
+    
+    for endpoint in &domain.endpoints {
+        pending.extend(endpoint.response_type.as_ref().map(|response| response.schema.clone()));
+        for param in &endpoint.parameters {
+            let schema = param.schema.as_ref().or(param.items.as_deref());
+            pending.extend(schema.and_then(referenced_schema_name));
+        }
+    }
+    
+    let mut closure = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        let Some(schema) = schemas.get(&name) else { continue };
+        if !closure.insert(name) {
+            continue;
+        }
+        pending.extend(schema.properties.values().filter_map(|prop| prop.reference.clone()));
+        pending.extend(schema.variants.iter().map(|variant| variant.schema.clone()));
     }
+    
+    closure
+}
+
+/// Relative Dart import of `to_file` from a file in `from_dir`, both relative to the project root
+fn relative_import(from_dir: &Path, to_file: &Path) -> String {
+    let from: Vec<_> = from_dir.components().collect();
+    let to: Vec<_> = to_file.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    
+    let mut parts = vec!["..".to_string(); from.len() - common];
+    parts.extend(to[common..].iter().map(|part| part.as_os_str().to_string_lossy().to_string()));
+    parts.join("/")
 }
 
-/// Generate model classes for a domain
-fn generate_domain_models(
+/// Generate the models, entities and enums of one group, returning the files
+/// written relative to the project root
+fn generate_models(
     project_dir: &Path,
-    feature_name: &str,
-    domain: &Domain,
-    schemas: &HashMap<String, SchemaInfo>,
-) -> Result<()> {
-    // Create models directory
-    let models_dir = project_dir.join("lib/features").join(feature_name).join("data/models");
-    fs::create_dir_all(&models_dir).context("Failed to create models directory")?;
+    group: &ModelGroup,
+    layout: &ModelLayout,
+    force: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let members = layout.members(group);
+    if members.is_empty() {
+        return Ok(files);
+    }
     
-    // Create entities directory structure under core/entities/{feature_name}
-    let core_entities_dir = project_dir.join("lib/core/entities").join(feature_name);
-    fs::create_dir_all(&core_entities_dir).context("Failed to create core entities directory")?;
+    // Create models and entities directories
+    vfs::create_dir_all(project_dir.join(group.models_dir())).context("Failed to create models directory")?;
+    vfs::create_dir_all(project_dir.join(group.entities_dir())).context("Failed to create core entities directory")?;
     
-    // Find all response types used in the domain
-    let mut domain_models = HashSet::new();
+    // Named enums in the group and the inline enums of its models
+    let enums: Vec<&EnumInfo> = members.iter().flat_map(|schema| &schema.enums).collect();
+    if !enums.is_empty() {
+        let feature_name = match group {
+            ModelGroup::Feature(feature_name) => Some(feature_name.as_str()),
+            ModelGroup::Shared => None,
+        };
+        generate_enums_file(&project_dir.join(group.enums_file()), feature_name, &enums, force)?;
+        files.push(group.enums_file());
+        println!("  ✓ Generated {} enums", style(enums.len()).bold());
+    }
     
-    for endpoint in &domain.endpoints {
-        if let Some(response_type) = &endpoint.response_type {
-            domain_models.insert(response_type.clone());
-        }
+    // Models with dates or binary fields share the JSON converters in core/utils
+    let needs_converters = members.iter().any(|schema| schema.has_converted_field());
+    let converters_file = project_dir.join(JSON_CONVERTERS_FILE);
+    if needs_converters && !vfs::exists(&converters_file) {
+        let context = TemplateContext::for_project(&ProjectContext::load(project_dir)?);
+        generate_template_file("common/app_structure/core/utils/json_converters.dart.tmpl", &converters_file, &context, force)?;
     }
     
-    // Generate models and entities for the domain
-    for model_name in domain_models {
-        if let Some(schema) = schemas.get(&model_name) {
-            // Generate model (data layer)
-            let model_file_path = models_dir.join(format!("{}_model.dart", model_name.to_case(Case::Snake)));
-            generate_model_class(&model_file_path, schema)?;
-            println!("  ✓ Generated model: {}", style(&model_name).bold());
-            
-            // Generate entity (domain layer)
-            let entity_name = if model_name.ends_with("Model") {
-                model_name.replace("Model", "")
-            } else {
-                model_name.clone()
+    // Generate models and entities for the group
+    for schema in members.into_iter().filter(|schema| !schema.is_enum()) {
+        // Generate model (data layer)
+        generate_model_class(project_dir, schema, layout, force)?;
+        files.push(layout.model_file(&schema.name));
+        println!("  ✓ Generated model: {}", style(&schema.name).bold());
+        
+        // Generate entity (domain layer)
+        generate_entity_class(project_dir, schema, layout, force)?;
+        files.push(layout.entity_file(&schema.name));
+        println!("  ✓ Generated entity: {}", style(entity_name(&schema.name)).bold());
+    }
+    
+    Ok(files)
+}
+
+/// A `///` doc comment for generated Dart, one comment line per line of `text`
+fn doc_comment(indent: &str, text: &str) -> String {
+    text.trim_end()
+        .lines()
+        .map(|line| match line.trim_end() {
+            "" => format!("{}///\n", indent),
+            line => format!("{}/// {}\n", indent, line),
+        })
+        .collect()
+}
+
+/// Shared JSON converters, relative to the project root
+const JSON_CONVERTERS_FILE: &str = "lib/core/utils/json_converters.dart";
+
+/// Write the enums used by a group's models to its `enums.dart`
+///
+/// A feature's file starts from the common feature template, followed by one
+/// Dart enum per API enum. Every enum gets an `unknown` member that fields fall
+/// back to when the API sends a value the app does not know yet.
+fn generate_enums_file(
+    file_path: &Path,
+    feature_name: Option<&str>,
+    enums: &[&EnumInfo],
+    force: bool,
+) -> Result<()> {
+    let mut content = String::from("import 'package:json_annotation/json_annotation.dart';\n\n");
+    
+    if let Some(feature_name) = feature_name {
+        let context = TemplateContext {
+            feature: Some(FeatureContext::new(&FeatureParams::new(feature_name))),
+            ..TemplateContext::default()
+        };
+        content.push_str(&render_template("features/common/data/models/enums.dart.tmpl", &context)?);
+    }
+    
+    for info in enums {
+        content.push('\n');
+        
+        // Add documentation if available
+        content.push_str(&doc_comment("", &info.description));
+        
+        content.push_str(&format!("enum {} {{\n", info.name));
+        
+        let mut members = Vec::new();
+        for value in &info.values {
+            let (literal, member_source) = match value {
+                serde_json::Value::String(text) => (dart_string_literal(text), text.clone()),
+                other => (other.to_string(), other.to_string()),
             };
             
-            let entity_file_path = core_entities_dir.join(format!("{}.dart", entity_name.to_case(Case::Snake)));
-            generate_entity_class(&entity_file_path, schema, &entity_name)?;
-            println!("  ✓ Generated entity: {}", style(&entity_name).bold());
+            // Members must be unique even when values only differ in punctuation or case
+            let base = dart_identifier(&member_source);
+            let mut member = base.clone();
+            let mut suffix = 2;
+            while members.contains(&member) {
+                member = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+            
+            content.push_str(&format!("  @JsonValue({})\n", literal));
+            content.push_str(&format!("  {},\n", member));
+            members.push(member);
+        }
+        
+        // An API value named "unknown" doubles as the fallback
+        if !members.iter().any(|m| m == "unknown") {
+            content.push_str("  unknown,\n");
         }
+        content.push_str("}\n");
     }
     
+    // Hand-written enum extensions survive regeneration
+    content.push('\n');
+    content.push_str(&keep_region("", "extensions"));
+    
+    // Write to file
+    write_generated_file(file_path, &content, force)?;
+    
     Ok(())
 }
 
 /// Generate a Dart model class from a schema
 fn generate_model_class(
-    file_path: &Path,
+    project_dir: &Path,
     schema: &SchemaInfo,
+    layout: &ModelLayout,
+    force: bool,
 ) -> Result<()> {
+    if !schema.variants.is_empty() {
+        return generate_sealed_model_class(project_dir, schema, layout, force);
+    }
+    
     let class_name = format!("{}Model", schema.name);
-    let entity_name = schema.name.clone();
-    let feature_name = get_feature_name_from_file_path(file_path)?;
+    let entity_name = entity_name(&schema.name);
+    let models_dir = layout.group(&schema.name).models_dir();
+    let file_path = layout.model_file(&schema.name);
+    
+    // Collect imports of the entity and of every model, enum and converter the fields use
+    let mut imports = BTreeSet::from([relative_import(&models_dir, &layout.entity_file(&schema.name))]);
+    if schema.has_converted_field() {
+        imports.insert(relative_import(&models_dir, Path::new(JSON_CONVERTERS_FILE)));
+    }
+    for prop in schema.properties.values() {
+        if let Some(enums_file) = layout.enums_file(schema, prop) {
+            imports.insert(relative_import(&models_dir, &enums_file));
+        }
+        if let Some(name) = layout.model_reference(prop) {
+            imports.insert(relative_import(&models_dir, &layout.model_file(name)));
+        }
+    }
+    let has_nested_models = schema.properties.values().any(|prop| layout.model_reference(prop).is_some());
     
     let mut content = String::new();
     
     // Add imports
-    content.push_str("import 'package:json_annotation/json_annotation.dart';\n");
+    if schema.has_binary_field() {
+        content.push_str("import 'dart:typed_data';\n\n");
+    }
+    content.push_str("import 'package:json_annotation/json_annotation.dart';\n\n");
+    for import in &imports {
+        content.push_str(&format!("import '{}';\n", import));
+    }
+    content.push_str(&keep_region("", "imports"));
+    content.push('\n');
+    content.push_str(&format!(
+        "part '{}.g.dart';\n\n",
+        file_path.file_stem().unwrap_or_default().to_string_lossy()
+    ));
     
     // Add documentation if available
-    if !schema.description.is_empty() {
-        content.push_str("/// ");
-        content.push_str(&schema.description);
-        content.push_str("\n");
-    }
+    content.push_str(&doc_comment("", &schema.description));
     
-    // Add class annotation
-    content.push_str("@JsonSerializable()\n");
+    // Add class annotation; nested models must be serialized with their own toJson
+    if has_nested_models {
+        content.push_str("@JsonSerializable(explicitToJson: true)\n");
+    } else {
+        content.push_str("@JsonSerializable()\n");
+    }
     content.push_str(&format!("class {} {{\n", class_name));
     
     // Add properties
     for (name, prop) in &schema.properties {
         // Add documentation if available
-        if !prop.description.is_empty() {
-            content.push_str("  /// ");
-            content.push_str(&prop.description);
-            content.push_str("\n");
-        }
+        content.push_str(&doc_comment("  ", &prop.description));
         
-        // Add JsonKey annotation if the property name is not a valid Dart identifier
-        if name.contains("-") || name.contains(".") || name.contains(" ") {
-            content.push_str(&format!("  @JsonKey(name: '{}')\n", name));
+        // Keep the JSON key when the Dart field name differs from it, and fall
+        // back to the enum's unknown member for values added to the API later
+        let dart_name = dart_identifier(name);
+        let mut key_options = Vec::new();
+        if dart_name != *name {
+            key_options.push(format!("name: '{}'", name));
+        }
+        if let Some(enum_name) = &prop.enum_name {
+            key_options.push(format!("unknownEnumValue: {}.unknown", enum_name));
+        }
+        if !key_options.is_empty() {
+            content.push_str(&format!("  @JsonKey({})\n", key_options.join(", ")));
+        }
+        if let Some(converter) = prop.converter {
+            content.push_str(&format!("  @{}()\n", converter));
         }
         
         // Add property declaration
-        let dart_type = if prop.is_nullable {
-            format!("{}?", prop.type_name)
-        } else {
-            prop.type_name.clone()
-        };
+        let mut dart_type = layout.model_type(prop);
+        if prop.is_nullable {
+            dart_type.push('?');
+        }
         
-        let dart_name = name.to_case(Case::Camel);
         content.push_str(&format!("  final {} {};\n\n", dart_type, dart_name));
     }
     
     // Add constructor
     content.push_str(&format!("  {}({{\n", class_name));
-    for (name, _prop) in &schema.properties {
-        let dart_name = name.to_case(Case::Camel);
+    for name in schema.properties.keys() {
+        let dart_name = dart_identifier(name);
         if schema.required.contains(name) {
             content.push_str(&format!("    required this.{},\n", dart_name));
         } else {
@@ -535,132 +2511,311 @@ fn generate_model_class(
     content.push_str(&format!("  factory {}.fromJson(Map<String, dynamic> json) => _${}FromJson(json);\n\n", class_name, class_name));
     content.push_str(&format!("  Map<String, dynamic> toJson() => _${}ToJson(this);\n", class_name));
     
-    // Add entity conversion methods
-    update_model_class_with_entity_conversion(&mut content, schema, &class_name, &entity_name, &feature_name);
+    // Add entity conversion methods
+    update_model_class_with_entity_conversion(&mut content, schema, &class_name, &entity_name, layout);
+    
+    // Close class definition, leaving room for hand-written members
+    content.push('\n');
+    content.push_str(&keep_region("  ", "members"));
+    content.push_str("}\n");
+    
+    // Write to file
+    write_generated_file(&project_dir.join(file_path), &content, force)?;
+    
+    Ok(())
+}
+
+/// Generate a Dart entity class from a schema
+fn generate_entity_class(
+    project_dir: &Path,
+    schema: &SchemaInfo,
+    layout: &ModelLayout,
+    force: bool,
+) -> Result<()> {
+    if !schema.variants.is_empty() {
+        return generate_sealed_entity_class(project_dir, schema, layout, force);
+    }
+    
+    let entity_name = entity_name(&schema.name);
+    let entities_dir = layout.group(&schema.name).entities_dir();
+    
+    // Collect imports of the entities and enums the fields use
+    let mut imports = BTreeSet::new();
+    for prop in schema.properties.values() {
+        if let Some(enums_file) = layout.enums_file(schema, prop) {
+            imports.insert(relative_import(&entities_dir, &enums_file));
+        }
+        if let Some(name) = layout.model_reference(prop) {
+            imports.insert(relative_import(&entities_dir, &layout.entity_file(name)));
+        }
+    }
+    
+    let mut content = String::new();
+    
+    // Add imports if needed
+    if schema.has_binary_field() {
+        content.push_str("import 'dart:typed_data';\n");
+    }
+    if schema.has_date_time_field() || schema.has_list_field() {
+        content.push_str("import 'package:intl/intl.dart';\n");
+    }
+    for import in &imports {
+        content.push_str(&format!("import '{}';\n", import));
+    }
+    content.push_str(&keep_region("", "imports"));
+    content.push('\n');
+    
+    // Add documentation if available
+    content.push_str(&doc_comment("", &schema.description));
+    
+    // Add entity class
+    content.push_str(&format!("class {} {{\n", entity_name));
+    
+    // Add properties
+    for (name, prop) in &schema.properties {
+        // Add documentation if available
+        content.push_str(&doc_comment("  ", &prop.description));
+        
+        // Add property declaration
+        let mut dart_type = layout.entity_type(prop);
+        if prop.is_nullable {
+            dart_type.push('?');
+        }
+        
+        let dart_name = dart_identifier(name);
+        content.push_str(&format!("  final {} {};\n\n", dart_type, dart_name));
+    }
+    
+    // Add constructor
+    content.push_str(&format!("  const {}({{\n", entity_name));
+    for name in schema.properties.keys() {
+        let dart_name = dart_identifier(name);
+        if schema.required.contains(name) {
+            content.push_str(&format!("    required this.{},\n", dart_name));
+        } else {
+            content.push_str(&format!("    this.{},\n", dart_name));
+        }
+    }
+    content.push_str("  });\n\n");
+    
+    // Add copyWith method for immutability
+    content.push_str(&format!("  {} copyWith({{\n", entity_name));
+    for (name, prop) in &schema.properties {
+        let dart_name = dart_identifier(name);
+        let mut dart_type = layout.entity_type(prop);
+        if prop.is_nullable {
+            dart_type.push('?');
+        }
+        content.push_str(&format!("    {}? {},\n", dart_type, dart_name));
+    }
+    content.push_str("  }) {\n");
+    content.push_str(&format!("    return {}(\n", entity_name));
+    for name in schema.properties.keys() {
+        let dart_name = dart_identifier(name);
+        content.push_str(&format!("      {}: {} ?? this.{},\n", dart_name, dart_name, dart_name));
+    }
+    content.push_str("    );\n");
+    content.push_str("  }\n\n");
+
+    // Close class, leaving room for hand-written members
+    content.push_str(&keep_region("  ", "members"));
+    content.push_str("}\n");
+    
+    // Write the content to the file
+    write_generated_file(&project_dir.join(layout.entity_file(&schema.name)), &content, force)?;
+    
+    Ok(())
+}
+
+/// Generate a sealed Dart model class for a polymorphic schema
+///
+/// Each variant is wrapped in a subclass holding the variant's own model, and
+/// `fromJson` picks the variant from the discriminator property.
+fn generate_sealed_model_class(
+    project_dir: &Path,
+    schema: &SchemaInfo,
+    layout: &ModelLayout,
+    force: bool,
+) -> Result<()> {
+    let class_name = format!("{}Model", schema.name);
+    let entity_name = entity_name(&schema.name);
+    let models_dir = layout.group(&schema.name).models_dir();
+    let discriminator = schema.discriminator.as_deref().unwrap_or_default();
+    
+    let mut imports = BTreeSet::from([relative_import(&models_dir, &layout.entity_file(&schema.name))]);
+    for variant in &schema.variants {
+        imports.insert(relative_import(&models_dir, &layout.model_file(&variant.schema)));
+    }
+    
+    let mut content = String::new();
+    
+    // Add imports
+    for import in &imports {
+        content.push_str(&format!("import '{}';\n", import));
+    }
+    content.push_str(&keep_region("", "imports"));
+    content.push('\n');
+    
+    // Add documentation if available
+    content.push_str(&doc_comment("", &schema.description));
+    
+    content.push_str(&format!("sealed class {} {{\n", class_name));
+    content.push_str(&format!("  const {}();\n\n", class_name));
+    
+    // Pick the variant from the discriminator value
+    content.push_str(&format!("  factory {}.fromJson(Map<String, dynamic> json) {{\n", class_name));
+    content.push_str(&format!("    switch (json['{}']) {{\n", discriminator));
+    for variant in &schema.variants {
+        content.push_str(&format!("      case '{}':\n", variant.value));
+        content.push_str(&format!(
+            "        return {}{}Model({}Model.fromJson(json));\n",
+            schema.name, variant.schema, variant.schema
+        ));
+    }
+    content.push_str("      default:\n");
+    content.push_str(&format!(
+        "        throw ArgumentError.value(json['{}'], '{}', 'Unknown {} type');\n",
+        discriminator, discriminator, class_name
+    ));
+    content.push_str("    }\n");
+    content.push_str("  }\n\n");
+    
+    content.push_str("  Map<String, dynamic> toJson();\n\n");
     
-    // Close class definition
+    // Add entity conversion methods
+    content.push_str("  // Convert to Entity\n");
+    content.push_str(&format!("  {} toEntity();\n\n", entity_name));
+    content.push_str(&format!("  // Create from Entity\n  factory {}.fromEntity({} entity) {{\n", class_name, entity_name));
+    content.push_str("    return switch (entity) {\n");
+    for variant in &schema.variants {
+        content.push_str(&format!(
+            "      {}{}(:final value) => {}{}Model({}Model.fromEntity(value)),\n",
+            entity_name, variant.schema, schema.name, variant.schema, variant.schema
+        ));
+    }
+    content.push_str("    };\n");
+    content.push_str("  }\n\n");
+    content.push_str(&keep_region("  ", "members"));
     content.push_str("}\n");
     
+    // Add a subclass wrapping each variant's model
+    for variant in &schema.variants {
+        let wrapper_name = format!("{}{}Model", schema.name, variant.schema);
+        content.push_str(&format!("\nfinal class {} extends {} {{\n", wrapper_name, class_name));
+        content.push_str(&format!("  final {}Model value;\n\n", variant.schema));
+        content.push_str(&format!("  const {}(this.value);\n\n", wrapper_name));
+        content.push_str("  @override\n");
+        content.push_str("  Map<String, dynamic> toJson() => value.toJson();\n\n");
+        content.push_str("  @override\n");
+        content.push_str(&format!(
+            "  {} toEntity() => {}{}(value.toEntity());\n",
+            entity_name, entity_name, variant.schema
+        ));
+        content.push_str("}\n");
+    }
+    
     // Write to file
-    fs::write(file_path, content).context("Failed to write model file")?;
+    write_generated_file(&project_dir.join(layout.model_file(&schema.name)), &content, force)?;
     
     Ok(())
 }
 
-/// Generate a Dart entity class from a schema
-fn generate_entity_class(
-    file_path: &Path,
+/// Generate a sealed Dart entity class for a polymorphic schema
+fn generate_sealed_entity_class(
+    project_dir: &Path,
     schema: &SchemaInfo,
-    entity_name: &str,
+    layout: &ModelLayout,
+    force: bool,
 ) -> Result<()> {
+    let entity_name = entity_name(&schema.name);
+    let entities_dir = layout.group(&schema.name).entities_dir();
+    
+    let imports: BTreeSet<String> = schema.variants
+        .iter()
+        .map(|variant| relative_import(&entities_dir, &layout.entity_file(&variant.schema)))
+        .collect();
+    
     let mut content = String::new();
     
-    // Add imports if needed
-    if schema.has_date_time_field() || schema.has_list_field() {
-        content.push_str("import 'package:intl/intl.dart';\n");
+    // Add imports
+    for import in &imports {
+        content.push_str(&format!("import '{}';\n", import));
     }
-    content.push_str("\n");
+    content.push_str(&keep_region("", "imports"));
+    content.push('\n');
     
     // Add documentation if available
-    if !schema.description.is_empty() {
-        content.push_str("/// ");
-        content.push_str(&schema.description);
-        content.push_str("\n");
-    }
-    
-    // Add entity class
-    content.push_str(&format!("class {} {{\n", entity_name));
-    
-    // Add properties
-    for (name, prop) in &schema.properties {
-        // Add documentation if available
-        if !prop.description.is_empty() {
-            content.push_str("  /// ");
-            content.push_str(&prop.description);
-            content.push_str("\n");
-        }
-        
-        // Add property declaration
-        let dart_type = if prop.is_nullable {
-            format!("{}?", prop.type_name)
-        } else {
-            prop.type_name.clone()
-        };
-        
-        let dart_name = name.to_case(Case::Camel);
-        content.push_str(&format!("  final {} {};\n\n", dart_type, dart_name));
-    }
+    content.push_str(&doc_comment("", &schema.description));
     
-    // Add constructor
-    content.push_str(&format!("  const {}({{\n", entity_name));
-    for (name, _prop) in &schema.properties {
-        let dart_name = name.to_case(Case::Camel);
-        if schema.required.contains(name) {
-            content.push_str(&format!("    required this.{},\n", dart_name));
-        } else {
-            content.push_str(&format!("    this.{},\n", dart_name));
-        }
-    }
-    content.push_str("  });\n\n");
+    content.push_str(&format!("sealed class {} {{\n", entity_name));
+    content.push_str(&format!("  const {}();\n\n", entity_name));
+    content.push_str(&keep_region("  ", "members"));
+    content.push_str("}\n");
     
-    // Add copyWith method for immutability
-    content.push_str(&format!("  {} copyWith({{\n", entity_name));
-    for (name, prop) in &schema.properties {
-        let dart_name = name.to_case(Case::Camel);
-        let dart_type = if prop.is_nullable {
-            format!("{}?", prop.type_name)
-        } else {
-            prop.type_name.clone()
-        };
-        content.push_str(&format!("    {}? {},\n", dart_type, dart_name));
-    }
-    content.push_str("  }) {\n");
-    content.push_str(&format!("    return {}(\n", entity_name));
-    for (name, _) in &schema.properties {
-        let dart_name = name.to_case(Case::Camel);
-        content.push_str(&format!("      {}: {} ?? this.{},\n", dart_name, dart_name, dart_name));
+    for variant in &schema.variants {
+        let wrapper_name = format!("{}{}", entity_name, variant.schema);
+        content.push_str(&format!("\nfinal class {} extends {} {{\n", wrapper_name, entity_name));
+        content.push_str(&format!("  final {} value;\n\n", self::entity_name(&variant.schema)));
+        content.push_str(&format!("  const {}(this.value);\n", wrapper_name));
+        content.push_str("}\n");
     }
-    content.push_str("    );\n");
-    content.push_str("  }\n");
-
-    // Close class
-    content.push_str("}\n");
     
     // Write the content to the file
-    fs::write(file_path, content).context("Failed to write entity file")?;
+    write_generated_file(&project_dir.join(layout.entity_file(&schema.name)), &content, force)?;
     
     Ok(())
 }
 
-/// Generate datasource and repository implementations for a domain
+/// Generate datasource and repository implementations for a domain, returning
+/// the files that are regenerated from the spec relative to the project root
+///
+/// The local datasource is only a starting point for hand-written caching, so
+/// an existing one is left alone.
 fn generate_domain_datasources(
     project_dir: &Path,
     feature_name: &str,
     domain: &Domain,
-    _spec: &SwaggerSpec,
-) -> Result<()> {
+    type_mappings: &TypeMappings,
+    layout: &ModelLayout,
+    force: bool,
+) -> Result<Vec<PathBuf>> {
     // Paths for the files we'll generate
-    let repo_dir = project_dir.join("lib/features").join(feature_name).join("data/repository");
-    let datasource_dir = project_dir.join("lib/features").join(feature_name).join("data/datasources");
+    let repo_dir = Path::new("lib/features").join(feature_name).join("data/repository");
+    let datasource_dir = Path::new("lib/features").join(feature_name).join("data/datasources");
     
-    fs::create_dir_all(&repo_dir).context("Failed to create repository directory")?;
-    fs::create_dir_all(&datasource_dir).context("Failed to create datasources directory")?;
+    vfs::create_dir_all(project_dir.join(&repo_dir)).context("Failed to create repository directory")?;
+    vfs::create_dir_all(project_dir.join(&datasource_dir)).context("Failed to create datasources directory")?;
     
     // Generate remote datasource
     let remote_ds_path = datasource_dir.join(format!("{}_remote_datasource.dart", feature_name));
-    generate_remote_datasource(&remote_ds_path, feature_name, domain)?;
+    generate_remote_datasource(&project_dir.join(&remote_ds_path), feature_name, domain, type_mappings, layout, force)?;
     
     // Generate local datasource
-    let local_ds_path = datasource_dir.join(format!("{}_local_datasource.dart", feature_name));
-    generate_local_datasource(&local_ds_path, feature_name, domain)?;
+    let local_ds_path = project_dir.join(&datasource_dir).join(format!("{}_local_datasource.dart", feature_name));
+    if !vfs::exists(&local_ds_path) {
+        generate_local_datasource(&local_ds_path, feature_name, domain)?;
+    }
     
     // Generate repository
     let repo_path = repo_dir.join(format!("{}_repository.dart", feature_name));
-    generate_repository(&repo_path, feature_name, domain)?;
+    generate_repository(&project_dir.join(&repo_path), feature_name, domain, type_mappings, layout, force)?;
     
     println!("✅ Generated data layer for domain: {}", style(&domain.name).bold());
     
-    Ok(())
+    Ok(vec![remote_ds_path, repo_path])
+}
+
+/// Schemas used by a domain's endpoint signatures, as responses or as parameters
+fn endpoint_schemas(domain: &Domain) -> (BTreeSet<String>, BTreeSet<String>) {
+    let responses = domain.endpoints.iter()
+        .filter_map(|endpoint| endpoint.response_type.as_ref().map(|response| response.schema.clone()))
+        .collect();
+    let parameters = domain.endpoints.iter()
+        .flat_map(|endpoint| &endpoint.parameters)
+        .filter_map(|param| param.schema.as_ref().or(param.items.as_deref()))
+        .filter_map(referenced_schema_name)
+        .collect();
+    (responses, parameters)
 }
 
 /// Generate remote datasource implementation
@@ -668,25 +2823,36 @@ fn generate_remote_datasource(
     file_path: &Path,
     feature_name: &str,
     domain: &Domain,
+    type_mappings: &TypeMappings,
+    layout: &ModelLayout,
+    force: bool,
 ) -> Result<()> {
     let pascal_name = feature_name.to_case(Case::Pascal);
     
+    // Import the model of every response and parameter schema
+    let datasources_dir = ModelGroup::Feature(feature_name.to_string()).models_dir().with_file_name("datasources");
+    let (responses, parameters) = endpoint_schemas(domain);
+    let model_imports: BTreeSet<String> = responses.iter()
+        .chain(&parameters)
+        .filter(|name| layout.is_model(name))
+        .map(|name| format!("import '{}';\n", relative_import(&datasources_dir, &layout.model_file(name))))
+        .collect();
+    
     let mut content = format!(
         "import 'package:dio/dio.dart';
 import 'package:injectable/injectable.dart';
 
 import '../../../../core/api/api_client.dart';
-import '../models/{}_model.dart';
-
+{}{}
 abstract class {}_RemoteDatasource {{
-", feature_name, pascal_name);
+", model_imports.into_iter().collect::<String>(), keep_region("", "imports"), pascal_name);
 
     // Add method signatures for each endpoint
     for endpoint in &domain.endpoints {
-        let method_name = endpoint.operation_id.to_case(Case::Camel);
+        let method_name = &endpoint.method_name;
         let return_type = endpoint.response_type
             .as_ref()
-            .map(|t| format!("Future<{}>", t))
+            .map(|response| format!("Future<{}>", response.dart_type(|t| format!("{}Model", t))))
             .unwrap_or_else(|| "Future<void>".to_string());
         
         // Add method signature
@@ -694,16 +2860,8 @@ abstract class {}_RemoteDatasource {{
         content.push_str(&format!("  {} {}(", return_type, method_name));
         
         // Add parameters
-        let params: Vec<String> = endpoint.parameters
-            .iter()
-            .filter(|p| p.r#in == "path" || p.r#in == "query" || p.r#in == "body")
-            .map(|p| {
-                let required = if p.required { "required " } else { "" };
-                format!("{}dynamic {}", required, p.name)
-            })
-            .collect();
-        
-        content.push_str(&params.join(", "));
+        let params = dart_parameters(endpoint, type_mappings);
+        content.push_str(&dart_parameter_list(&params));
         content.push_str(");\n");
     }
     
@@ -721,68 +2879,86 @@ class {}_RemoteDatasourceImpl implements {}_RemoteDatasource {{
 
     // Add method implementations
     for endpoint in &domain.endpoints {
-        let method_name = endpoint.operation_id.to_case(Case::Camel);
+        let method_name = &endpoint.method_name;
         let return_type = endpoint.response_type
             .as_ref()
-            .map(|t| format!("Future<{}>", t))
+            .map(|response| format!("Future<{}>", response.dart_type(|t| format!("{}Model", t))))
             .unwrap_or_else(|| "Future<void>".to_string());
         
         // Add method implementation
-        content.push_str(&format!("  @override\n"));
+        content.push_str("  @override\n");
         content.push_str(&format!("  {} {}(", return_type, method_name));
         
         // Add parameters
-        let params: Vec<String> = endpoint.parameters
-            .iter()
-            .filter(|p| p.r#in == "path" || p.r#in == "query" || p.r#in == "body")
-            .map(|p| {
-                let required = if p.required { "required " } else { "" };
-                format!("{}dynamic {}", required, p.name)
-            })
-            .collect();
-        
-        content.push_str(&params.join(", "));
+        let params = dart_parameters(endpoint, type_mappings);
+        content.push_str(&dart_parameter_list(&params));
         content.push_str(") async {\n");
         
         // Implementation
         let method = endpoint.method.to_lowercase();
-        let path = endpoint.path.clone();
+        let path = dart_path(&endpoint.path, &params);
         
         content.push_str(&format!(
-            "    final response = await _apiClient.{}('{}', ",
+            "    final response = await _apiClient.{}({}",
             method, path
         ));
         
         // Add query params if any
-        let query_params: Vec<String> = endpoint.parameters
+        let query_params: Vec<String> = params
             .iter()
-            .filter(|p| p.r#in == "query")
-            .map(|p| format!("'{}': {}", p.name, p.name))
+            .filter(|p| p.param.r#in == "query")
+            .map(|p| p.map_entry())
             .collect();
         
         if !query_params.is_empty() {
-            content.push_str("queryParameters: {");
+            content.push_str(", queryParameters: {");
             content.push_str(&query_params.join(", "));
-            content.push_str("}, ");
+            content.push('}');
+        }
+        
+        // Add header params if any
+        let header_params: Vec<String> = params
+            .iter()
+            .filter(|p| p.param.r#in == "header")
+            .map(|p| p.map_entry())
+            .collect();
+        
+        if !header_params.is_empty() {
+            content.push_str(", options: Options(headers: {");
+            content.push_str(&header_params.join(", "));
+            content.push_str("})");
         }
         
         // Add body if any
-        let body_param = endpoint.parameters
+        let form_fields: Vec<String> = params
             .iter()
-            .find(|p| p.r#in == "body");
+            .filter(|p| p.param.r#in == "formData")
+            .map(|p| p.map_entry())
+            .collect();
         
-        if let Some(body) = body_param {
-            content.push_str(&format!("data: {}", body.name));
+        if let Some(body) = params.iter().find(|p| p.param.r#in == "body") {
+            content.push_str(&format!(", data: {}", body.body_expression()));
+        } else if !form_fields.is_empty() {
+            content.push_str(", data: FormData.fromMap({");
+            content.push_str(&form_fields.join(", "));
+            content.push_str("})");
         }
         
         content.push_str(");\n");
         
         // Return statement based on return type
         if let Some(response_type) = &endpoint.response_type {
-            content.push_str(&format!(
-                "    return {}Model.fromJson(response.data);\n",
-                response_type
-            ));
+            if response_type.is_list {
+                content.push_str(&format!(
+                    "    return (response.data as List).map((item) => {}Model.fromJson(item as Map<String, dynamic>)).toList();\n",
+                    response_type.schema
+                ));
+            } else {
+                content.push_str(&format!(
+                    "    return {}Model.fromJson(response.data);\n",
+                    response_type.schema
+                ));
+            }
         } else {
             content.push_str("    return;\n");
         }
@@ -790,11 +2966,12 @@ class {}_RemoteDatasourceImpl implements {}_RemoteDatasource {{
         content.push_str("  }\n\n");
     }
     
-    // Close the class
-    content.push_str("}");
+    // Close the class, leaving room for hand-written members
+    content.push_str(&keep_region("  ", "members"));
+    content.push_str("}\n");
     
     // Write to file
-    fs::write(file_path, content).context("Failed to write remote datasource file")?;
+    write_generated_file(file_path, &content, force)?;
     
     Ok(())
 }
@@ -811,8 +2988,6 @@ fn generate_local_datasource(
         "import 'package:injectable/injectable.dart';
 import 'package:shared_preferences/shared_preferences.dart';
 
-import '../models/{}_model.dart';
-
 abstract class {}_LocalDatasource {{
   Future<void> cache{}Data(dynamic data);
   Future<dynamic> get{}Data();
@@ -846,7 +3021,6 @@ class {}_LocalDatasourceImpl implements {}_LocalDatasource {{
   }}
 }}
 ",
-        feature_name, // Import path
         pascal_name, // Class name abstract
         pascal_name, // Method cache
         pascal_name, // Method get
@@ -863,7 +3037,7 @@ class {}_LocalDatasourceImpl implements {}_LocalDatasource {{
     );
     
     // Write to file
-    fs::write(file_path, content).context("Failed to write local datasource file")?;
+    vfs::write(file_path, content).context("Failed to write local datasource file")?;
     
     Ok(())
 }
@@ -873,17 +3047,31 @@ fn generate_repository(
     file_path: &Path,
     feature_name: &str,
     domain: &Domain,
+    type_mappings: &TypeMappings,
+    layout: &ModelLayout,
+    force: bool,
 ) -> Result<()> {
     let pascal_name = feature_name.to_case(Case::Pascal);
     
-    let mut content = generate_repository_formatted_content(feature_name, &pascal_name);
+    // Responses are returned as entities, while parameters are still passed as models
+    let repo_dir = ModelGroup::Feature(feature_name.to_string()).models_dir().with_file_name("repository");
+    let (responses, parameters) = endpoint_schemas(domain);
+    let mut imports: BTreeSet<String> = responses.iter()
+        .filter(|name| layout.is_model(name))
+        .map(|name| relative_import(&repo_dir, &layout.entity_file(name)))
+        .collect();
+    imports.extend(parameters.iter()
+        .filter(|name| layout.is_model(name))
+        .map(|name| relative_import(&repo_dir, &layout.model_file(name))));
+    
+    let mut content = generate_repository_formatted_content(feature_name, &pascal_name, &imports);
     
     // Add method signatures for each endpoint
     for endpoint in &domain.endpoints {
-        let method_name = endpoint.operation_id.to_case(Case::Camel);
+        let method_name = &endpoint.method_name;
         let entity_type = endpoint.response_type
             .as_ref()
-            .map(|t| if t.ends_with("Model") { t[0..t.len()-5].to_string() } else { t.clone() });
+            .map(|response| response.dart_type(entity_name));
             
         let return_type = entity_type
             .as_ref()
@@ -895,16 +3083,8 @@ fn generate_repository(
         content.push_str(&format!("  {} {}(", return_type, method_name));
         
         // Add parameters
-        let params: Vec<String> = endpoint.parameters
-            .iter()
-            .filter(|p| p.r#in == "path" || p.r#in == "query" || p.r#in == "body")
-            .map(|p| {
-                let required = if p.required { "required " } else { "" };
-                format!("{}dynamic {}", required, p.name)
-            })
-            .collect();
-        
-        content.push_str(&params.join(", "));
+        let params = dart_parameters(endpoint, type_mappings);
+        content.push_str(&dart_parameter_list(&params));
         content.push_str(");\n");
     }
     
@@ -930,25 +3110,17 @@ class {}_RepositoryImpl implements {}_Repository {{
     
     // Add method implementations
     for endpoint in &domain.endpoints {
-        let method_name = endpoint.operation_id.to_case(Case::Camel);
-        let response_type = endpoint.response_type.as_deref().unwrap_or("void");
-        let return_type = format!("Future<Either<Failure, {}>>", response_type);
+        let method_name = &endpoint.method_name;
+        let entity_type = endpoint.response_type.as_ref().map(|response| response.dart_type(entity_name));
+        let return_type = format!("Future<Either<Failure, {}>>", entity_type.as_deref().unwrap_or("void"));
         
         // Add method implementation
-        content.push_str(&format!("  @override\n"));
+        content.push_str("  @override\n");
         content.push_str(&format!("  {} {}(", return_type, method_name));
         
         // Add parameters
-        let params: Vec<String> = endpoint.parameters
-            .iter()
-            .filter(|p| p.r#in == "path" || p.r#in == "query" || p.r#in == "body")
-            .map(|p| {
-                let required = if p.required { "required " } else { "" };
-                format!("{}dynamic {}", required, p.name)
-            })
-            .collect();
-        
-        content.push_str(&params.join(", "));
+        let params = dart_parameters(endpoint, type_mappings);
+        content.push_str(&dart_parameter_list(&params));
         content.push_str(") async {\n");
         
         // Error handling implementation
@@ -957,20 +3129,23 @@ class {}_RepositoryImpl implements {}_Repository {{
         // Call to remote datasource
         content.push_str(&format!("      final modelResult = await _remoteDatasource.{}(", method_name));
         
-        // Add parameter names
-        let param_names: Vec<String> = endpoint.parameters
+        // Pass every parameter on by name
+        let param_names: Vec<String> = params
             .iter()
-            .filter(|p| p.r#in == "path" || p.r#in == "query" || p.r#in == "body")
-            .map(|p| p.name.clone())
+            .map(|p| format!("{0}: {0}", p.name))
             .collect();
         
         content.push_str(&param_names.join(", "));
         content.push_str(");\n");
         
         // Convert model to entity if there is a response type
-        if let Some(_model_type) = &endpoint.response_type {
+        if let Some(response_type) = &endpoint.response_type {
             // Convert model to entity
-            content.push_str(&format!("      final entity = modelResult.toEntity();\n"));
+            if response_type.is_list {
+                content.push_str("      final entity = modelResult.map((model) => model.toEntity()).toList();\n");
+            } else {
+                content.push_str("      final entity = modelResult.toEntity();\n");
+            }
             
             // Cache result if needed
             if endpoint.method == "GET" {
@@ -978,10 +3153,10 @@ class {}_RepositoryImpl implements {}_Repository {{
             }
             
             // Return successful entity
-            content.push_str(&format!("      return Right(entity);\n"));
+            content.push_str("      return Right(entity);\n");
         } else {
             // Return successful void result
-            content.push_str(&format!("      return const Right(null);\n"));
+            content.push_str("      return const Right(null);\n");
         }
         
         // Error handling
@@ -994,56 +3169,40 @@ class {}_RepositoryImpl implements {}_Repository {{
         content.push_str("  }\n\n");
     }
     
-    // Close the class
-    content.push_str("}");
+    // Close the class, leaving room for hand-written members
+    content.push_str(&keep_region("  ", "members"));
+    content.push_str("}\n");
     
     // Write to file
-    fs::write(file_path, content).context("Failed to write repository file")?;
+    write_generated_file(file_path, &content, force)?;
     
     Ok(())
 }
 
-/// Helper function to extract feature name from file path
-fn get_feature_name_from_file_path(file_path: &Path) -> Result<String> {
-    // Extract feature name from path like "lib/features/{feature_name}/data/models/..."
-    let path_str = file_path.to_string_lossy();
-    let features_pattern = "lib/features/";
-    let data_pattern = "/data/";
-    
-    if let Some(start_idx) = path_str.find(features_pattern) {
-        let feature_start = start_idx + features_pattern.len();
-        if let Some(end_idx) = path_str[feature_start..].find(data_pattern) {
-            let feature_name = &path_str[feature_start..feature_start + end_idx];
-            return Ok(feature_name.to_string());
-        }
-    }
-    
-    // Default fallback
-    Ok("unknown".to_string())
-}
-
 /// Update the model class generation to include to/from entity methods
+///
+/// Fields holding other models are converted with those models' own methods.
 fn update_model_class_with_entity_conversion(
     content: &mut String,
     schema: &SchemaInfo,
     model_name: &str,
     entity_name: &str,
-    feature_name: &str
+    layout: &ModelLayout,
 ) {
-    // Add entity import
-    content.push_str(&format!("import 'package:{{package_name}}/core/entities/{}/{}.dart';\n\n", 
-        feature_name,
-        entity_name.to_case(Case::Snake)
-    ));
-    
     // After the toJson method, add toEntity method
     content.push_str(&format!("\n  // Convert to Entity\n  {} toEntity() {{\n", entity_name));
     content.push_str(&format!("    return {}(\n", entity_name));
     
     // Add properties
-    for (name, _) in &schema.properties {
-        let dart_name = name.to_case(Case::Camel);
-        content.push_str(&format!("      {0}: {0},\n", dart_name));
+    for (name, prop) in &schema.properties {
+        let dart_name = dart_identifier(name);
+        let access = if prop.is_nullable { "?." } else { "." };
+        let value = match layout.model_reference(prop) {
+            Some(_) if prop.is_list => format!("{}{}map((e) => e.toEntity()).toList()", dart_name, access),
+            Some(_) => format!("{}{}toEntity()", dart_name, access),
+            None => dart_name.clone(),
+        };
+        content.push_str(&format!("      {}: {},\n", dart_name, value));
     }
     
     content.push_str("    );\n");
@@ -1054,9 +3213,19 @@ fn update_model_class_with_entity_conversion(
     content.push_str(&format!("    return {}(\n", model_name));
     
     // Add properties
-    for (name, _) in &schema.properties {
-        let dart_name = name.to_case(Case::Camel);
-        content.push_str(&format!("      {0}: entity.{0},\n", dart_name));
+    for (name, prop) in &schema.properties {
+        let dart_name = dart_identifier(name);
+        let access = if prop.is_nullable { "?." } else { "." };
+        let value = match layout.model_reference(prop) {
+            Some(model) if prop.is_list => format!("entity.{}{}map({}Model.fromEntity).toList()", dart_name, access, model),
+            Some(model) if prop.is_nullable => format!(
+                "entity.{0} == null ? null : {1}Model.fromEntity(entity.{0}!)",
+                dart_name, model
+            ),
+            Some(model) => format!("{}Model.fromEntity(entity.{})", model, dart_name),
+            None => format!("entity.{}", dart_name),
+        };
+        content.push_str(&format!("      {}: {},\n", dart_name, value));
     }
     
     content.push_str("    );\n");
@@ -1066,22 +3235,750 @@ fn update_model_class_with_entity_conversion(
 /// Fix repository import format
 fn generate_repository_formatted_content(
     feature_name: &str, 
-    pascal_name: &str
+    pascal_name: &str,
+    imports: &BTreeSet<String>,
 ) -> String {
+    let imports: String = imports.iter().map(|import| format!("import '{}';\n", import)).collect();
     format!(
         "import 'package:dartz/dartz.dart';
 import 'package:dio/dio.dart';
 import 'package:injectable/injectable.dart';
 
 import '../../../../core/failures/failure.dart';
-import '../../../../core/entities/{0}/{0}.dart';
-import '../datasources/{0}_remote_datasource.dart';
+{2}import '../datasources/{0}_remote_datasource.dart';
 import '../datasources/{0}_local_datasource.dart';
-import '../models/{0}_model.dart';
-
+{3}
 abstract class {1}_Repository {{
 ",
         feature_name,
-        pascal_name
+        pascal_name,
+        imports,
+        keep_region("", "imports")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PETSTORE_OAS3: &str = r##"{
+        "openapi": "3.0.3",
+        "info": { "title": "Petstore", "version": "1.0.0" },
+        "paths": {
+            "/pets/{petId}": {
+                "parameters": [ { "$ref": "#/components/parameters/PetId" } ],
+                "get": {
+                    "tags": ["pets"],
+                    "operationId": "getPet",
+                    "responses": { "200": { "$ref": "#/components/responses/PetResponse" } }
+                },
+                "put": {
+                    "tags": ["pets"],
+                    "operationId": "updatePet",
+                    "requestBody": { "$ref": "#/components/requestBodies/PetBody" },
+                    "responses": {
+                        "201": {
+                            "description": "Updated",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+                        }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "Pet": {
+                    "type": "object",
+                    "required": ["id"],
+                    "properties": { "id": { "type": "integer" }, "tag": { "type": ["string", "null"] } }
+                }
+            },
+            "parameters": {
+                "PetId": { "name": "petId", "in": "path", "required": true, "schema": { "type": "integer" } }
+            },
+            "responses": {
+                "PetResponse": {
+                    "description": "A pet",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+                }
+            },
+            "requestBodies": {
+                "PetBody": {
+                    "required": true,
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+                }
+            }
+        }
+    }"##;
+
+    fn find_endpoint<'a>(domains: &'a [Domain], operation_id: &str) -> &'a Endpoint {
+        domains.iter()
+            .flat_map(|d| &d.endpoints)
+            .find(|e| e.operation_id == operation_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_openapi3_components_are_resolved() {
+        let spec: SwaggerSpec = serde_json::from_str(PETSTORE_OAS3).unwrap();
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+
+        let get_pet = find_endpoint(&domains, "getPet");
+        assert_eq!(get_pet.response_signature(), "Pet");
+        assert_eq!(get_pet.parameters.len(), 1);
+        assert_eq!(get_pet.parameters[0].name, "petId");
+        assert_eq!(get_pet.parameters[0].r#in, "path");
+
+        let update_pet = find_endpoint(&domains, "updatePet");
+        assert_eq!(update_pet.response_signature(), "Pet");
+        let body = update_pet.parameters.iter().find(|p| p.r#in == "body").unwrap();
+        assert!(body.required);
+        assert_eq!(body.schema.as_ref().unwrap().reference.as_deref(), Some("#/components/schemas/Pet"));
+
+        let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+        assert_eq!(schemas["Pet"].properties["tag"].type_name, "String");
+    }
+
+    #[test]
+    fn test_swagger2_response_schema() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "swagger": "2.0",
+            "paths": {
+                "/users": {
+                    "get": {
+                        "tags": ["users"],
+                        "operationId": "getUser",
+                        "parameters": [ { "$ref": "#/parameters/Limit" } ],
+                        "responses": { "200": { "schema": { "$ref": "#/definitions/User" } } }
+                    }
+                }
+            },
+            "parameters": { "Limit": { "name": "limit", "in": "query", "type": "integer" } },
+            "definitions": { "User": { "type": "object" } }
+        }"##).unwrap();
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        let get_user = find_endpoint(&domains, "getUser");
+        assert_eq!(get_user.response_signature(), "User");
+        assert_eq!(get_user.parameters[0].name, "limit");
+    }
+
+    #[test]
+    fn test_spec_format_detection() {
+        assert_eq!(SpecFormat::from_extension("specs/openapi.YAML"), Some(SpecFormat::Yaml));
+        assert_eq!(SpecFormat::from_extension("https://api.example.com/openapi.json?v=2"), Some(SpecFormat::Json));
+        assert_eq!(SpecFormat::from_extension("https://api.example.com/docs"), None);
+
+        assert_eq!(SpecFormat::from_content_type("application/json; charset=utf-8"), Some(SpecFormat::Json));
+        assert_eq!(SpecFormat::from_content_type("application/vnd.oai.openapi+yaml"), Some(SpecFormat::Yaml));
+        assert_eq!(SpecFormat::from_content_type("text/plain"), None);
+
+        assert_eq!(SpecFormat::sniff("\n  {\"openapi\": \"3.0.0\"}"), SpecFormat::Json);
+        assert_eq!(SpecFormat::sniff("openapi: 3.0.0\n"), SpecFormat::Yaml);
+    }
+
+    #[test]
+    fn test_parse_yaml_spec() {
+        let yaml = "openapi: 3.0.0\ninfo:\n  title: Pets\n  version: '1'\npaths:\n  /pets:\n    get:\n      tags: [pets]\n      operationId: listPets\n      responses:\n        200:\n          description: ok\n";
+        let spec: SwaggerSpec = parse_spec(yaml, SpecFormat::Yaml, "openapi.yaml").unwrap();
+        assert_eq!(spec.info.title, "Pets");
+        assert!(spec.paths["/pets"].get.as_ref().unwrap().responses.contains_key("200"));
+
+        let error = parse_spec::<SwaggerSpec>("openapi: 3.0.0\npaths: [\n", SpecFormat::Yaml, "bad.yaml").unwrap_err();
+        assert!(error.to_string().contains("bad.yaml as YAML at line"), "{}", error);
+    }
+
+    #[test]
+    fn test_unresolved_reference_is_an_error() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/a": { "get": { "tags": ["a"], "parameters": [ { "$ref": "#/components/parameters/Missing" } ] } }
+            }
+        }"##).unwrap();
+
+        assert!(extract_domains(&spec, GroupingStrategy::Tag).is_err());
+    }
+
+    #[test]
+    fn test_circular_local_reference_is_an_error() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/a": { "get": { "tags": ["a"], "parameters": [ { "$ref": "#/components/parameters/A" } ] } }
+            },
+            "components": {
+                "parameters": {
+                    "A": { "$ref": "#/components/parameters/B" },
+                    "B": { "$ref": "#/components/parameters/A" },
+                    "Self": { "$ref": "#/components/parameters/Self" }
+                }
+            }
+        }"##).unwrap();
+
+        let Err(error) = extract_domains(&spec, GroupingStrategy::Tag) else { panic!("cycle was resolved") };
+        assert_eq!(
+            format!("{:#}", error),
+            "GET /a: Circular $ref: #/components/parameters/A -> #/components/parameters/B -> #/components/parameters/A"
+        );
+
+        let parameter = &spec.components.as_ref().unwrap().parameters["Self"];
+        let error = spec.resolve_parameter(parameter).unwrap_err();
+        assert!(error.to_string().starts_with("Circular $ref"), "{}", error);
+    }
+
+    #[test]
+    fn test_bundle_split_spec() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(dir.join("paths")).unwrap();
+        fs::create_dir_all(dir.join("schemas")).unwrap();
+
+        fs::write(dir.join("openapi.yaml"), "openapi: 3.0.0\ninfo: { title: Users, version: '1' }\npaths:\n  /users/{id}:\n    $ref: paths/users.yaml\n  /loop:\n    $ref: 'schemas/common.yaml#/Loop'\ncomponents:\n  schemas:\n    Address:\n      $ref: schemas/address.json\n").unwrap();
+        fs::write(dir.join("paths/users.yaml"), "get:\n  tags: [users]\n  operationId: getUser\n  responses:\n    '200':\n      content:\n        application/json:\n          schema:\n            $ref: '../schemas/common.yaml#/User'\n").unwrap();
+        fs::write(dir.join("schemas/common.yaml"), "User:\n  type: object\n  properties:\n    friends:\n      type: array\n      items: { $ref: '#/User' }\n    address: { $ref: 'address.json' }\nLoop: { $ref: '#/Loop2' }\nLoop2: { $ref: '#/Loop' }\n").unwrap();
+        fs::write(dir.join("schemas/address.json"), r#"{ "type": "object", "properties": { "city": { "type": "string" } } }"#).unwrap();
+
+        // Reference cycles between non-schema objects cannot be bundled
+        let error = load_swagger_spec(SwaggerSource::File(dir.join("openapi.yaml"))).unwrap_err();
+        assert!(format!("{:#}", error).contains("Circular $ref"), "{:#}", error);
+
+        let root = fs::read_to_string(dir.join("openapi.yaml")).unwrap();
+        fs::write(dir.join("openapi.yaml"), root.replace("  /loop:\n    $ref: 'schemas/common.yaml#/Loop'\n", "")).unwrap();
+        let spec = load_swagger_spec(SwaggerSource::File(dir.join("openapi.yaml"))).unwrap();
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        assert_eq!(find_endpoint(&domains, "getUser").response_signature(), "User");
+
+        // The root's own name is kept for a schema defined in another file
+        let schemas: HashMap<&str, &SwaggerSchema> = spec.all_schemas().map(|(name, schema)| (name.as_str(), schema)).collect();
+        assert_eq!(schemas.len(), 2, "{:?}", schemas.keys());
+        let user = schemas["User"];
+        assert_eq!(user.properties["address"].reference.as_deref(), Some("#/components/schemas/Address"));
+        let friends = user.properties["friends"].items.as_ref().unwrap();
+        assert_eq!(friends.reference.as_deref(), Some("#/components/schemas/User"));
+    }
+
+    #[test]
+    fn test_schema_ref_name() {
+        assert_eq!(schema_ref_name("#/components/schemas/Pet").as_deref(), Some("Pet"));
+        assert_eq!(schema_ref_name("schemas/common.yaml#/User").as_deref(), Some("User"));
+        assert_eq!(schema_ref_name("schemas/order_item.yaml").as_deref(), Some("OrderItem"));
+    }
+
+    #[test]
+    fn test_grouping_strategies() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets": { "get": { "tags": ["pets"], "operationId": "pets_list", "responses": {} } },
+                "/users/{id}": { "get": { "operationId": "getUser", "responses": {} } },
+                "/{tenant}/orders": { "post": { "operationId": "orders.create", "responses": {} } }
+            }
+        }"##).unwrap();
+
+        let names = |grouping| {
+            let mut names: Vec<String> = extract_domains(&spec, grouping).unwrap()
+                .into_iter()
+                .map(|domain| domain.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Untagged operations fall back to their path prefix instead of being dropped
+        assert_eq!(names(GroupingStrategy::Tag), vec!["orders", "pets", "users"]);
+        assert_eq!(names(GroupingStrategy::PathPrefix), vec!["orders", "pets", "users"]);
+        assert_eq!(names(GroupingStrategy::OperationId), vec!["orders", "pets", "users"]);
+        assert_eq!(names(GroupingStrategy::Single), vec!["api"]);
+
+        assert_eq!(path_prefix("/"), "api");
+        assert_eq!(operation_id_prefix("users_list").as_deref(), Some("users"));
+        assert_eq!(operation_id_prefix("listUsers"), None);
+    }
+
+    #[test]
+    fn test_output_follows_spec_order() {
+        let spec = r##"{
+            "openapi": "3.0.0",
+            "tags": [ { "name": "stores" }, { "name": "pets" } ],
+            "paths": {
+                "/pets/{petId}": { "get": { "tags": ["pets"], "operationId": "getPet", "parameters": [
+                    { "name": "petId", "in": "path", "required": true, "schema": { "type": "integer" } }
+                ], "responses": {
+                    "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } } }
+                } } },
+                "/pets": { "get": { "tags": ["pets"], "operationId": "listPets", "responses": {} } },
+                "/stores": { "get": { "tags": ["stores"], "operationId": "listStores", "responses": {} } }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "properties": { "zeta": { "type": "string" }, "alpha": { "type": "string" }, "mid": { "type": "integer" } }
+                    }
+                }
+            }
+        }"##;
+
+        let generate = || {
+            let spec: SwaggerSpec = parse_spec(spec, SpecFormat::Json, "spec").unwrap();
+            let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+            let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+            let layout = ModelLayout::new(&schemas, &domains);
+
+            let temp = tempfile::tempdir().unwrap();
+            let dir = temp.path().to_path_buf();
+            fs::create_dir_all(dir.join("lib/features/pets/data/models")).unwrap();
+            fs::create_dir_all(dir.join("lib/core/entities/pets")).unwrap();
+            generate_model_class(&dir, &schemas["Pet"], &layout, false).unwrap();
+            let datasource = dir.join("datasource.dart");
+            generate_remote_datasource(&datasource, "pets", &domains[1], &TypeMappings::default(), &layout, false).unwrap();
+            let output = fs::read_to_string(dir.join("lib/features/pets/data/models/pet_model.dart")).unwrap()
+                + &fs::read_to_string(datasource).unwrap();
+
+            let names: Vec<String> = domains.into_iter().map(|domain| domain.name).collect();
+            (names, output)
+        };
+
+        let (names, output) = generate();
+        assert_eq!(names, vec!["stores", "pets"]);
+        let position = |needle: &str| output.find(needle).unwrap_or_else(|| panic!("{} missing", needle));
+        assert!(position("final String? zeta;") < position("final String? alpha;"));
+        assert!(position("final String? alpha;") < position("final int? mid;"));
+        assert!(position("getPet(") < position("listPets("));
+
+        // Regenerating gives byte-identical output
+        for _ in 0..5 {
+            assert_eq!(generate().1, output);
+        }
+    }
+
+    #[test]
+    fn test_method_names() {
+        assert_eq!(synthesize_method_name("GET", "/users/{id}"), "getUserById");
+        assert_eq!(synthesize_method_name("GET", "/users"), "getUsers");
+        assert_eq!(synthesize_method_name("DELETE", "/categories/{category-id}/addresses/{addressId}"), "deleteCategoryByCategoryIdAddressByAddressId");
+        assert_eq!(singular("boxes"), "box");
+        assert_eq!(singular("status"), "status");
+        assert_eq!(singular("statuses"), "status");
+        assert_eq!(singular("heroes"), "hero");
+        assert_eq!(singular("shoes"), "shoe");
+        assert_eq!(singular("canoes"), "canoe");
+        assert_eq!(singular("movies"), "movie");
+        assert_eq!(singular("cookies"), "cookie");
+        assert_eq!(singular("categories"), "category");
+        assert_eq!(singular("addresses"), "address");
+        assert_eq!(synthesize_method_name("GET", "/movies/{id}"), "getMovieById");
+        assert_eq!(synthesize_method_name("GET", "/orders/{id}/statuses"), "getOrderByIdStatuses");
+        assert_eq!(synthesize_method_name("GET", "/heroes/{heroId}"), "getHeroByHeroId");
+
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/users": {
+                    "get": { "tags": ["users"], "operationId": "users", "responses": {} },
+                    "post": { "tags": ["users"], "responses": {} }
+                },
+                "/users/{id}": {
+                    "get": { "tags": ["users"], "operationId": "users", "responses": {} },
+                    "put": { "tags": ["users"], "operationId": "deleteUserById", "responses": {} },
+                    "delete": { "tags": ["users"], "operationId": "--", "responses": {} }
+                }
+            }
+        }"##).unwrap();
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        let names: Vec<&str> = domains[0].endpoints.iter().map(|e| e.method_name.as_str()).collect();
+        assert_eq!(names, vec!["users", "postUsers", "getUserById", "deleteUserById", "deleteUserById2"]);
+    }
+
+    #[test]
+    fn test_update_regenerates_generated_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        let feature_dir = dir.join("lib/features/pets");
+        fs::create_dir_all(feature_dir.join("presentation")).unwrap();
+        fs::write(feature_dir.join("presentation/pets_page.dart"), "// hand-written").unwrap();
+
+        let spec_path = dir.join("openapi.json");
+        let write_spec = |paths: &str, pet_properties: &str, tag: bool| {
+            let tag = if tag { r#", "Tag": { "type": "object", "properties": { "name": { "type": "string" } } }"# } else { "" };
+            fs::write(&spec_path, format!(
+                r#"{{ "openapi": "3.0.0", "paths": {{ {} }}, "components": {{ "schemas": {{
+                    "Pet": {{ "type": "object", "properties": {{ {} }} }}{}
+                }} }} }}"#,
+                paths, pet_properties, tag
+            )).unwrap();
+        };
+        let update = |force| generate_api_features(
+            &dir,
+            SwaggerSource::File(spec_path.clone()),
+            None,
+            true,
+            &TypeMappings::default(),
+            GroupingStrategy::Tag,
+            UpdateMode { update: true, force },
+        );
+
+        let get_pet = r##""/pets": { "get": { "tags": ["pets"], "operationId": "getPet", "responses": {
+            "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } } }
+        } } }"##;
+        write_spec(get_pet, r##""tag": { "$ref": "#/components/schemas/Tag" }"##, true);
+        update(false).unwrap();
+        assert!(dir.join("lib/features/pets/data/models/tag_model.dart").exists());
+
+        // Code written inside a keep region is carried over
+        let model_path = dir.join("lib/features/pets/data/models/pet_model.dart");
+        let model = fs::read_to_string(&model_path).unwrap();
+        fs::write(&model_path, model.replace(
+            "  // flutter_lazy:keep-end members",
+            "  bool get isNamed => true;\n  // flutter_lazy:keep-end members",
+        )).unwrap();
+
+        // Tag is no longer used, Pet changes shape and a new endpoint appears
+        let delete_pet = r#""/pets/{id}": { "delete": { "tags": ["pets"], "operationId": "deletePet", "parameters": [
+            { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } }
+        ], "responses": {} } }"#;
+        write_spec(&format!("{}, {}", get_pet, delete_pet), r#""name": { "type": "string" }"#, false);
+        update(false).unwrap();
+        let model = fs::read_to_string(&model_path).unwrap();
+
+        // Edits to the generated code itself are refused
+        fs::write(&model_path, model.replace("final String? name;", "final String name;")).unwrap();
+        let edited = update(false);
+        update(true).unwrap();
+        let forced = fs::read_to_string(&model_path).unwrap();
+
+        let snapshot = ApiSnapshot::load(&dir).unwrap().unwrap();
+        let datasource = fs::read_to_string(feature_dir.join("data/datasources/pets_remote_datasource.dart")).unwrap();
+        let tag_model_exists = dir.join("lib/features/pets/data/models/tag_model.dart").exists();
+        let page = fs::read_to_string(feature_dir.join("presentation/pets_page.dart")).unwrap();
+
+        assert!(!tag_model_exists);
+        assert_eq!(page, "// hand-written");
+        assert!(model.contains("bool get isNamed => true;") && !model.contains("TagModel"), "{}", model);
+        assert!(format!("{:#}", edited.unwrap_err()).contains("Refusing to overwrite"));
+        assert!(forced.contains("final String? name;") && forced.contains("bool get isNamed => true;"), "{}", forced);
+        assert!(datasource.contains("deletePet({required int id})"), "{}", datasource);
+        assert_eq!(snapshot.endpoints["pets"].keys().collect::<Vec<_>>(), vec!["DELETE /pets/{id}", "GET /pets"]);
+        assert!(!snapshot.schemas.contains_key("Tag"));
+        assert!(snapshot.files.contains(Path::new("lib/features/pets/data/models/pet_model.dart")));
+    }
+
+    #[test]
+    fn test_dart_identifier() {
+        assert_eq!(dart_identifier("pet-id"), "petId");
+        assert_eq!(dart_identifier("X-Request-ID"), "xRequestId");
+        assert_eq!(dart_identifier("user_name"), "userName");
+        assert_eq!(dart_identifier("default"), "defaultValue");
+        assert_eq!(dart_identifier("2fa"), "value2Fa");
+    }
+
+    #[test]
+    fn test_typed_datasource_parameters() {
+        let mut spec: serde_json::Value = serde_json::from_str(PETSTORE_OAS3).unwrap();
+        spec["paths"]["/pets/{petId}"]["put"]["parameters"] = serde_json::json!([
+            { "name": "X-Request-ID", "in": "header", "required": true, "schema": { "type": "string" } },
+            { "name": "dry-run", "in": "query", "schema": { "type": "boolean" } }
+        ]);
+        let spec: SwaggerSpec = serde_json::from_value(spec).unwrap();
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pets_remote_datasource.dart");
+        let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+        let layout = ModelLayout::new(&schemas, &domains);
+        generate_remote_datasource(&path, "pets", &domains[0], &TypeMappings::default(), &layout, false).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+
+        assert!(content.contains("updatePet({required int petId, required String xRequestId, bool? dryRun, required PetModel body})"), "{}", content);
+        assert!(content.contains("_apiClient.put('/pets/$petId', queryParameters: {if (dryRun != null) 'dry-run': dryRun}, options: Options(headers: {'X-Request-ID': xRequestId}), data: body.toJson());"), "{}", content);
+    }
+
+    #[test]
+    fn test_dart_path() {
+        assert_eq!(dart_path("/pets/{pet-id}/photos", &[]), "'/pets/$petId/photos'");
+        assert_eq!(dart_path("/files/{name}_{version}", &[]), "'/files/${name}_$version'");
+    }
+
+    #[test]
+    fn test_schema_composition() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {},
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["petType"],
+                        "properties": { "petType": { "type": "string" }, "name": { "type": "string" } }
+                    },
+                    "Dog": {
+                        "allOf": [
+                            { "$ref": "#/components/schemas/Pet" },
+                            { "type": "object", "required": ["bark"], "properties": { "bark": { "type": "boolean" } } }
+                        ]
+                    },
+                    "Cat": {
+                        "allOf": [ { "$ref": "#/components/schemas/Pet" } ],
+                        "properties": { "owner": { "allOf": [ { "$ref": "#/components/schemas/Dog" } ] } }
+                    },
+                    "AnyPet": {
+                        "description": "Any pet.\nDogs and cats only.",
+                        "oneOf": [ { "$ref": "#/components/schemas/Dog" }, { "$ref": "#/components/schemas/Cat" } ],
+                        "discriminator": { "propertyName": "petType", "mapping": { "dog": "#/components/schemas/Dog" } }
+                    }
+                }
+            }
+        }"##).unwrap();
+
+        let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+
+        // allOf parts are flattened into one property set
+        let dog = &schemas["Dog"];
+        assert_eq!(dog.properties.len(), 3);
+        assert!(dog.required.contains(&"petType".to_string()) && dog.required.contains(&"bark".to_string()));
+        assert!(!dog.properties["bark"].is_nullable);
+        assert_eq!(schemas["Cat"].properties["owner"].type_name, "Dog");
+
+        // oneOf with a discriminator becomes a sealed class
+        let any_pet = &schemas["AnyPet"];
+        assert_eq!(any_pet.discriminator.as_deref(), Some("petType"));
+        let values: Vec<&str> = any_pet.variants.iter().map(|v| v.value.as_str()).collect();
+        assert_eq!(values, vec!["dog", "Cat"]);
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        let models_dir = dir.join("lib/core/models");
+        fs::create_dir_all(&models_dir).unwrap();
+        generate_model_class(&dir, any_pet, &ModelLayout::new(&schemas, &[]), false).unwrap();
+        let content = fs::read_to_string(models_dir.join("any_pet_model.dart")).unwrap();
+        generate_entity_class(&dir, any_pet, &ModelLayout::new(&schemas, &[]), false).unwrap();
+        let entity = fs::read_to_string(dir.join("lib/core/entities/shared/any_pet.dart")).unwrap();
+
+        assert!(content.contains("import '../entities/shared/any_pet.dart';\nimport 'cat_model.dart';"), "{}", content);
+        assert!(content.contains("/// Any pet.\n/// Dogs and cats only.\nsealed class AnyPetModel {"), "{}", content);
+        assert!(entity.contains("/// Any pet.\n/// Dogs and cats only.\n"), "{}", entity);
+        assert!(content.contains("switch (json['petType']) {\n      case 'dog':\n        return AnyPetDogModel(DogModel.fromJson(json));"), "{}", content);
+        assert!(content.contains("final class AnyPetCatModel extends AnyPetModel {"), "{}", content);
+    }
+
+    #[test]
+    fn test_enum_generation() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets": { "get": { "tags": ["pets"], "operationId": "getPet", "responses": {
+                    "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } } }
+                } } }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "description": "A pet.\nMulti-line description.",
+                        "properties": {
+                            "status": { "type": "string", "enum": ["available", "sold-out", null], "description": "Sale status.\nSet by the store." },
+                            "size": { "$ref": "#/components/schemas/Size" }
+                        }
+                    },
+                    "Size": { "type": "integer", "enum": [1, 2], "description": "A pet size.\n\nMulti-line description.\n" }
+                }
+            }
+        }"##).unwrap();
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+        assert!(schemas["Size"].is_enum());
+        assert_eq!(schemas["Pet"].properties["status"].type_name, "PetStatus");
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        let layout = ModelLayout::new(&schemas, &domains);
+        generate_models(&dir, &ModelGroup::Feature("pets".to_string()), &layout, false).unwrap();
+        let models_dir = dir.join("lib/features/pets/data/models");
+        let enums = fs::read_to_string(models_dir.join("enums.dart")).unwrap();
+        let model = fs::read_to_string(models_dir.join("pet_model.dart")).unwrap();
+        let size_model_exists = models_dir.join("size_model.dart").exists();
+
+        assert!(enums.contains("enum PetsResult {"), "{}", enums);
+        assert!(enums.contains("enum PetStatus {\n  @JsonValue('available')\n  available,\n  @JsonValue('sold-out')\n  soldOut,\n  unknown,\n}"), "{}", enums);
+        assert!(enums.contains("/// A pet size.\n///\n/// Multi-line description.\nenum Size {\n  @JsonValue(1)\n  value1,"), "{}", enums);
+        assert!(model.contains("/// A pet.\n/// Multi-line description.\n"), "{}", model);
+        assert!(model.contains("  /// Sale status.\n  /// Set by the store.\n"), "{}", model);
+        assert!(model.contains("import 'enums.dart';"));
+        assert!(model.contains("@JsonKey(unknownEnumValue: PetStatus.unknown)\n  final PetStatus? status;"), "{}", model);
+        assert!(!size_model_exists);
+    }
+
+    #[test]
+    fn test_referenced_models_are_generated() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets": { "get": { "tags": ["pets"], "operationId": "getPet", "responses": {
+                    "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } } }
+                } } },
+                "/owners": { "post": {
+                    "tags": ["owners"],
+                    "operationId": "createOwner",
+                    "requestBody": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Owner" } } } }
+                } }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["tags"],
+                        "properties": {
+                            "tags": { "type": "array", "items": { "$ref": "#/components/schemas/Tag" } },
+                            "home": { "$ref": "#/components/schemas/Address" }
+                        }
+                    },
+                    "Owner": {
+                        "type": "object",
+                        "properties": { "address": { "$ref": "#/components/schemas/Address" } }
+                    },
+                    "Tag": { "type": "object", "properties": { "name": { "type": "string" } } },
+                    "Address": { "type": "object", "properties": { "city": { "type": "string" } } },
+                    "Unused": { "type": "object", "properties": { "id": { "type": "integer" } } }
+                }
+            }
+        }"##).unwrap();
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+        let pets = domains.iter().find(|domain| domain.name == "pets").unwrap();
+        let closure: Vec<String> = domain_schema_closure(pets, &schemas).into_iter().collect();
+        assert_eq!(closure, vec!["Address", "Pet", "Tag"]);
+
+        // Address is used by both domains, so it is generated once and shared
+        let layout = ModelLayout::new(&schemas, &domains);
+        assert_eq!(layout.group("Address"), ModelGroup::Shared);
+        assert_eq!(layout.group("Tag"), ModelGroup::Feature("pets".to_string()));
+        assert_eq!(layout.group("Owner"), ModelGroup::Feature("owners".to_string()));
+        assert!(!layout.groups.contains_key("Unused"));
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        generate_models(&dir, &ModelGroup::Shared, &layout, false).unwrap();
+        generate_models(&dir, &ModelGroup::Feature("pets".to_string()), &layout, false).unwrap();
+        let model = fs::read_to_string(dir.join("lib/features/pets/data/models/pet_model.dart")).unwrap();
+        let entity = fs::read_to_string(dir.join("lib/core/entities/pets/pet.dart")).unwrap();
+        let shared_exists = dir.join("lib/core/models/address_model.dart").exists();
+
+        assert!(shared_exists);
+        assert!(model.contains("import '../../../../core/models/address_model.dart';"), "{}", model);
+        assert!(model.contains("import 'tag_model.dart';"), "{}", model);
+        assert!(model.contains("part 'pet_model.g.dart';"), "{}", model);
+        assert!(model.contains("@JsonSerializable(explicitToJson: true)"), "{}", model);
+        assert!(model.contains("final List<TagModel> tags;"), "{}", model);
+        assert!(model.contains("tags: tags.map((e) => e.toEntity()).toList(),"), "{}", model);
+        assert!(model.contains("home: entity.home == null ? null : AddressModel.fromEntity(entity.home!),"), "{}", model);
+        assert!(entity.contains("import '../shared/address.dart';"), "{}", entity);
+        assert!(entity.contains("final List<Tag> tags;"), "{}", entity);
+    }
+
+    #[test]
+    fn test_array_responses_return_lists() {
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets": { "get": { "tags": ["pets"], "operationId": "listPets", "responses": {
+                    "200": { "content": { "application/json": { "schema": {
+                        "type": "array", "items": { "$ref": "#/components/schemas/Pet" }
+                    } } } }
+                } } },
+                "/toys": { "get": { "tags": ["pets"], "operationId": "listToys", "responses": {
+                    "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Toys" } } } }
+                } } }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": { "type": "object", "properties": { "name": { "type": "string" } } },
+                    "Toys": { "type": "array", "items": { "$ref": "#/components/schemas/Toy" } },
+                    "Toy": { "type": "object", "properties": { "name": { "type": "string" } } }
+                }
+            }
+        }"##).unwrap();
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        assert_eq!(find_endpoint(&domains, "listPets").response_signature(), "List<Pet>");
+        assert_eq!(find_endpoint(&domains, "listToys").response_signature(), "List<Toy>");
+
+        // Schemas reached only through array responses are generated
+        let schemas = extract_schemas(&spec, &TypeMappings::default()).unwrap();
+        let closure: Vec<String> = domain_schema_closure(&domains[0], &schemas).into_iter().collect();
+        assert_eq!(closure, vec!["Pet", "Toy"]);
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        let layout = ModelLayout::new(&schemas, &domains);
+        generate_models(&dir, &ModelGroup::Feature("pets".to_string()), &layout, false).unwrap();
+        let model = fs::read_to_string(dir.join("lib/features/pets/data/models/pet_model.dart")).unwrap();
+        assert!(model.contains("class PetModel"), "{}", model);
+
+        let datasource = dir.join("pets_remote_datasource.dart");
+        generate_remote_datasource(&datasource, "pets", &domains[0], &TypeMappings::default(), &layout, false).unwrap();
+        let datasource = fs::read_to_string(datasource).unwrap();
+        assert!(datasource.contains("Future<List<PetModel>> listPets();"), "{}", datasource);
+        assert!(
+            datasource.contains("return (response.data as List).map((item) => PetModel.fromJson(item as Map<String, dynamic>)).toList();"),
+            "{}",
+            datasource
+        );
+    }
+
+    #[test]
+    fn test_format_aware_type_mapping() {
+        let defaults = TypeMappings::default();
+        assert_eq!(defaults.dart_type("string", Some("date-time")), "DateTime");
+        assert_eq!(defaults.dart_type("integer", Some("int64")), "int");
+        assert_eq!(defaults.dart_type("number", Some("float")), "double");
+        assert_eq!(defaults.dart_type("string", Some("email")), "String");
+
+        let overrides = BTreeMap::from([("string:uuid".to_string(), "UuidValue".to_string())]);
+        assert_eq!(TypeMappings::new(&overrides).dart_type("string", Some("uuid")), "UuidValue");
+
+        let spec: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "paths": {
+                "/avatars": { "post": {
+                    "tags": ["users"],
+                    "operationId": "uploadAvatar",
+                    "requestBody": { "content": { "multipart/form-data": { "schema": {
+                        "type": "object",
+                        "required": ["file"],
+                        "properties": { "file": { "type": "string", "format": "binary" } }
+                    } } } }
+                } }
+            },
+            "components": {
+                "schemas": {
+                    "User": {
+                        "type": "object",
+                        "properties": {
+                            "born": { "type": "string", "format": "date" },
+                            "updatedAt": { "type": "string", "format": "date-time" },
+                            "avatar": { "type": "string", "format": "byte" }
+                        }
+                    }
+                }
+            }
+        }"##).unwrap();
+
+        let user = &extract_schemas(&spec, &defaults).unwrap()["User"];
+        assert_eq!(user.properties["born"].converter, Some("DateConverter"));
+        assert_eq!(user.properties["updatedAt"].type_name, "DateTime");
+        assert_eq!(user.properties["updatedAt"].converter, None);
+        assert_eq!(user.properties["avatar"].converter, Some("Base64Converter"));
+
+        let domains = extract_domains(&spec, GroupingStrategy::Tag).unwrap();
+        let upload = find_endpoint(&domains, "uploadAvatar");
+        let params = dart_parameters(upload, &defaults);
+        assert_eq!(params[0].declaration(), "required MultipartFile file");
+    }
+}
@@ -0,0 +1,339 @@
+// diff.rs
+// Breaking-change report between two versions of a Swagger/OpenAPI spec
+
+use anyhow::{Context, Result};
+use console::style;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::{
+    Endpoint, SchemaInfo, SwaggerSource, SwaggerSpec, TypeMappings,
+    dart_parameters, extract_schemas, read_swagger_spec,
+};
+
+/// Kind of difference between two versions of a spec
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ChangeKind {
+    OperationAdded,
+    OperationRemoved,
+    ResponseTypeChanged,
+    ParameterAdded,
+    ParameterRemoved,
+    ParameterRequirednessChanged,
+    ParameterTypeChanged,
+    SchemaAdded,
+    SchemaRemoved,
+    PropertyAdded,
+    PropertyRemoved,
+    PropertyRequirednessChanged,
+    PropertyTypeChanged,
+}
+
+/// One difference, and whether it breaks Dart code generated from the old spec
+#[derive(Debug, Serialize)]
+struct ApiChange {
+    kind: ChangeKind,
+    /// Operation (`GET /pets`) or schema the change is in
+    location: String,
+    description: String,
+    breaking: bool,
+}
+
+/// Every difference between two specs
+#[derive(Debug, Serialize)]
+struct ApiDiff {
+    old_version: String,
+    new_version: String,
+    breaking_changes: usize,
+    changes: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    fn push(&mut self, kind: ChangeKind, location: &str, description: String, breaking: bool) {
+        self.changes.push(ApiChange {
+            kind,
+            location: location.to_string(),
+            description,
+            breaking,
+        });
+    }
+}
+
+/// Compare two specs and print what changed, as text or as JSON
+pub fn diff_api_specs(
+    old: SwaggerSource,
+    new: SwaggerSource,
+    type_mappings: &TypeMappings,
+    json: bool,
+) -> Result<()> {
+    let old_spec = read_swagger_spec(&old, false).context("Failed to load the old spec")?;
+    let new_spec = read_swagger_spec(&new, false).context("Failed to load the new spec")?;
+    let diff = diff_specs(&old_spec, &new_spec, type_mappings)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff).context("Failed to serialize API diff")?);
+    } else {
+        print_diff(&diff);
+    }
+
+    Ok(())
+}
+
+fn print_diff(diff: &ApiDiff) {
+    println!(
+        "API diff: {} → {}",
+        style(&diff.old_version).bold(),
+        style(&diff.new_version).bold()
+    );
+
+    if diff.changes.is_empty() {
+        println!("\n✅ No differences found");
+        return;
+    }
+
+    if diff.breaking_changes > 0 {
+        println!(
+            "\n{} {} breaking changes:",
+            style("⚠️").yellow().bold(),
+            style(diff.breaking_changes).bold()
+        );
+        for change in diff.changes.iter().filter(|change| change.breaking) {
+            println!("  {} {}: {}", style("✗").red(), change.location, change.description);
+        }
+    }
+
+    let compatible = diff.changes.len() - diff.breaking_changes;
+    if compatible > 0 {
+        println!("\n{} compatible changes:", style(compatible).bold());
+        for change in diff.changes.iter().filter(|change| !change.breaking) {
+            println!("  {} {}: {}", style("✓").green(), change.location, change.description);
+        }
+    }
+}
+
+/// Endpoints of a spec keyed by `METHOD /path`, in spec order
+fn operations(spec: &SwaggerSpec) -> Result<IndexMap<String, Endpoint>> {
+    let mut result = IndexMap::new();
+    for (path, path_item) in &spec.paths {
+        for (method, operation) in path_item.operations() {
+            let endpoint = Endpoint::new(spec, path, path_item, method, operation)?;
+            result.insert(format!("{} {}", endpoint.method, endpoint.path), endpoint);
+        }
+    }
+    Ok(result)
+}
+
+fn diff_specs(old: &SwaggerSpec, new: &SwaggerSpec, type_mappings: &TypeMappings) -> Result<ApiDiff> {
+    let mut diff = ApiDiff {
+        old_version: old.info.version.clone(),
+        new_version: new.info.version.clone(),
+        breaking_changes: 0,
+        changes: Vec::new(),
+    };
+
+    // Removed operations take their generated methods with them
+    let old_operations = operations(old)?;
+    let new_operations = operations(new)?;
+    for (key, endpoint) in &new_operations {
+        match old_operations.get(key) {
+            Some(previous) => diff_operation(&mut diff, key, previous, endpoint, type_mappings),
+            None => diff.push(ChangeKind::OperationAdded, key, "operation added".to_string(), false),
+        }
+    }
+    for key in old_operations.keys().filter(|key| !new_operations.contains_key(*key)) {
+        diff.push(ChangeKind::OperationRemoved, key, "operation removed".to_string(), true);
+    }
+
+    // Removed schemas take their model classes with them
+    let old_schemas = extract_schemas(old, type_mappings)?;
+    let new_schemas = extract_schemas(new, type_mappings)?;
+    for (name, schema) in &new_schemas {
+        match old_schemas.get(name) {
+            Some(previous) => diff_schema(&mut diff, name, previous, schema),
+            None => diff.push(ChangeKind::SchemaAdded, name, "schema added".to_string(), false),
+        }
+    }
+    for name in old_schemas.keys().filter(|name| !new_schemas.contains_key(*name)) {
+        diff.push(ChangeKind::SchemaRemoved, name, "schema removed".to_string(), true);
+    }
+
+    diff.breaking_changes = diff.changes.iter().filter(|change| change.breaking).count();
+    Ok(diff)
+}
+
+fn diff_operation(
+    diff: &mut ApiDiff,
+    key: &str,
+    old: &Endpoint,
+    new: &Endpoint,
+    type_mappings: &TypeMappings,
+) {
+    let old_response = old.response_signature();
+    let new_response = new.response_signature();
+    if old_response != new_response {
+        diff.push(
+            ChangeKind::ResponseTypeChanged,
+            key,
+            format!("response type changed from {} to {}", old_response, new_response),
+            true,
+        );
+    }
+
+    // Parameters are matched by location and name, and compared as Dart method parameters
+    let parameters = |endpoint| -> IndexMap<(String, String), (String, bool)> {
+        dart_parameters(endpoint, type_mappings)
+            .into_iter()
+            .map(|p| ((p.param.r#in.clone(), p.param.name.clone()), (p.dart_type.clone(), p.is_required())))
+            .collect()
+    };
+    let old_params = parameters(old);
+    let new_params = parameters(new);
+
+    for ((location, name), (dart_type, required)) in &new_params {
+        let Some((old_type, old_required)) = old_params.get(&(location.clone(), name.clone())) else {
+            // Callers have to pass a new required parameter
+            let requirement = if *required { "required" } else { "optional" };
+            diff.push(
+                ChangeKind::ParameterAdded,
+                key,
+                format!("{} {} parameter '{}' added", requirement, location, name),
+                *required,
+            );
+            continue;
+        };
+
+        if old_type != dart_type {
+            diff.push(
+                ChangeKind::ParameterTypeChanged,
+                key,
+                format!("{} parameter '{}' changed from {} to {}", location, name, old_type, dart_type),
+                true,
+            );
+        }
+        if old_required != required {
+            // Making a parameter optional only widens its Dart type
+            let change = if *required { "optional to required" } else { "required to optional" };
+            diff.push(
+                ChangeKind::ParameterRequirednessChanged,
+                key,
+                format!("{} parameter '{}' changed from {}", location, name, change),
+                *required,
+            );
+        }
+    }
+    for (location, name) in old_params.keys().filter(|key| !new_params.contains_key(*key)) {
+        diff.push(
+            ChangeKind::ParameterRemoved,
+            key,
+            format!("{} parameter '{}' removed", location, name),
+            true,
+        );
+    }
+}
+
+fn diff_schema(diff: &mut ApiDiff, name: &str, old: &SchemaInfo, new: &SchemaInfo) {
+    for (prop_name, prop) in &new.properties {
+        let required = new.required.contains(prop_name);
+        let Some(previous) = old.properties.get(prop_name) else {
+            // A new required property is a new required constructor argument
+            let requirement = if required { "required" } else { "optional" };
+            diff.push(
+                ChangeKind::PropertyAdded,
+                name,
+                format!("{} property '{}' added", requirement, prop_name),
+                required,
+            );
+            continue;
+        };
+
+        if previous.type_name != prop.type_name {
+            diff.push(
+                ChangeKind::PropertyTypeChanged,
+                name,
+                format!("property '{}' changed from {} to {}", prop_name, previous.type_name, prop.type_name),
+                true,
+            );
+        }
+
+        // Either way the field's nullability changes, which breaks code using it
+        if old.required.contains(prop_name) != required {
+            let change = if required { "optional to required" } else { "required to optional" };
+            diff.push(
+                ChangeKind::PropertyRequirednessChanged,
+                name,
+                format!("property '{}' changed from {}", prop_name, change),
+                true,
+            );
+        }
+    }
+
+    for prop_name in old.properties.keys().filter(|prop_name| !new.properties.contains_key(*prop_name)) {
+        diff.push(
+            ChangeKind::PropertyRemoved,
+            name,
+            format!("property '{}' removed", prop_name),
+            true,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaking_changes() {
+        let old: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0" },
+            "paths": {
+                "/pets": { "get": { "operationId": "listPets", "parameters": [
+                    { "name": "limit", "in": "query", "schema": { "type": "integer" } }
+                ], "responses": {} } },
+                "/pets/{id}": { "delete": { "operationId": "deletePet", "responses": {} } }
+            },
+            "components": { "schemas": {
+                "Pet": { "type": "object", "required": ["name"], "properties": {
+                    "name": { "type": "string" }, "age": { "type": "integer" }, "tag": { "type": "string" }
+                } }
+            } }
+        }"##).unwrap();
+        let new: SwaggerSpec = serde_json::from_str(r##"{
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "2.0" },
+            "paths": {
+                "/pets": { "get": { "operationId": "listPets", "parameters": [
+                    { "name": "limit", "in": "query", "required": true, "schema": { "type": "integer" } },
+                    { "name": "offset", "in": "query", "schema": { "type": "integer" } }
+                ], "responses": {} } },
+                "/stores": { "get": { "operationId": "listStores", "responses": {} } }
+            },
+            "components": { "schemas": {
+                "Pet": { "type": "object", "required": ["name"], "properties": {
+                    "name": { "type": "string" }, "age": { "type": "number" }
+                } }
+            } }
+        }"##).unwrap();
+
+        let diff = diff_specs(&old, &new, &TypeMappings::default()).unwrap();
+        let summary: Vec<(ChangeKind, &str, bool)> = diff.changes
+            .iter()
+            .map(|change| (change.kind, change.location.as_str(), change.breaking))
+            .collect();
+
+        assert_eq!(summary, vec![
+            (ChangeKind::ParameterRequirednessChanged, "GET /pets", true),
+            (ChangeKind::ParameterAdded, "GET /pets", false),
+            (ChangeKind::OperationAdded, "GET /stores", false),
+            (ChangeKind::OperationRemoved, "DELETE /pets/{id}", true),
+            (ChangeKind::PropertyTypeChanged, "Pet", true),
+            (ChangeKind::PropertyRemoved, "Pet", true),
+        ]);
+        assert_eq!(diff.breaking_changes, 4);
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["changes"][0]["kind"], "parameter-requiredness-changed");
+        assert_eq!(json["new_version"], "2.0");
+    }
+}
@@ -0,0 +1,468 @@
+// templates.rs
+// Built-in templates with user overrides, rendered by Tera with a typed context
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use anyhow::{Context, Result};
+use convert_case::{Case, Casing};
+use serde::Serialize;
+use tera::Tera;
+use walkdir::WalkDir;
+
+use crate::config::FlavorConfig;
+use crate::features::FeatureParams;
+use crate::manifest::ProjectManifest;
+use crate::pubspec::Pubspec;
+use crate::vfs;
+
+/// Default templates compiled into the binary, by path relative to `templates/`
+static BUILT_IN: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/built_in_templates.rs"));
+
+/// Directory given with `--templates`
+static TEMPLATE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Use a directory whose templates take precedence over the built-in ones (`--templates`)
+pub fn set_template_dir(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        return Err(anyhow::anyhow!("Templates directory not found: {}", dir.display()));
+    }
+    *TEMPLATE_DIR.lock().unwrap() = Some(dir.to_path_buf());
+    Ok(())
+}
+
+/// Directories overriding built-in templates file by file, highest priority first:
+/// `--templates`, then `~/.config/flutter_lazy/templates`
+fn override_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = TEMPLATE_DIR.lock().unwrap().iter().cloned().collect();
+    if let Some(home) = std::env::var_os("HOME") {
+        let user_dir = PathBuf::from(home).join(".config/flutter_lazy/templates");
+        if user_dir.is_dir() {
+            dirs.push(user_dir);
+        }
+    }
+    dirs
+}
+
+/// A template from the first directory that has it, or else the built-in set
+fn find_in(dirs: &[PathBuf], path: &str) -> Result<Option<Vec<u8>>> {
+    for dir in dirs {
+        let file = dir.join(path);
+        if file.is_file() {
+            let content = fs::read(&file).with_context(|| format!("Failed to read template: {}", file.display()))?;
+            return Ok(Some(content));
+        }
+    }
+    Ok(BUILT_IN.iter().find(|(name, _)| *name == path).map(|(_, content)| content.to_vec()))
+}
+
+/// Every template under a directory by path relative to it, overrides replacing built-in files
+fn files_in(dirs: &[PathBuf], dir: &str) -> Result<BTreeMap<String, Vec<u8>>> {
+    let prefix = format!("{}/", dir.trim_end_matches('/'));
+    let mut files: BTreeMap<String, Vec<u8>> = BUILT_IN
+        .iter()
+        .filter_map(|(name, content)| Some((name.strip_prefix(&prefix)?.to_string(), content.to_vec())))
+        .collect();
+
+    // Lowest priority first, so higher ones replace its files
+    for root in dirs.iter().rev().map(|override_dir| override_dir.join(dir)).filter(|root| root.is_dir()) {
+        for entry in WalkDir::new(&root) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative = entry.path().strip_prefix(&root)?.to_string_lossy().replace('\\', "/");
+                let content = fs::read(entry.path())
+                    .with_context(|| format!("Failed to read template: {}", entry.path().display()))?;
+                files.insert(relative, content);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Every template set's version of a file, lowest priority first, for files
+/// like the versions catalog that overrides change entry by entry
+fn layers_in(dirs: &[PathBuf], path: &str) -> Result<Vec<String>> {
+    let mut layers = Vec::new();
+    if let Some((_, content)) = BUILT_IN.iter().find(|(name, _)| *name == path) {
+        layers.push(String::from_utf8(content.to_vec()).with_context(|| format!("Template is not UTF-8 text: {}", path))?);
+    }
+
+    for file in dirs.iter().rev().map(|dir| dir.join(path)).filter(|file| file.is_file()) {
+        layers.push(fs::read_to_string(&file).with_context(|| format!("Failed to read template: {}", file.display()))?);
+    }
+    Ok(layers)
+}
+
+/// A template's contents, if any template set has it
+pub fn find(path: &str) -> Result<Option<Vec<u8>>> {
+    find_in(&override_dirs(), path)
+}
+
+/// A template's text, failing when no template set has it
+pub fn read(path: &str) -> Result<String> {
+    let content = find(path)?.with_context(|| format!("Template file not found: {}", path))?;
+    String::from_utf8(content).with_context(|| format!("Template is not UTF-8 text: {}", path))
+}
+
+/// Every template under a directory, by path relative to it
+pub fn files(dir: &str) -> Result<BTreeMap<String, Vec<u8>>> {
+    files_in(&override_dirs(), dir)
+}
+
+/// Every template set's version of a file, the built-in one first
+pub fn layers(path: &str) -> Result<Vec<String>> {
+    layers_in(&override_dirs(), path)
+}
+
+/// A name in every case templates need, e.g. `{{ feature.pascal }}`
+#[derive(Debug, Clone, Serialize)]
+pub struct Names {
+    pub snake: String,
+    pub camel: String,
+    pub pascal: String,
+    pub kebab: String,
+    pub title: String,
+}
+
+impl Names {
+    pub fn new(name: &str) -> Self {
+        Self {
+            snake: name.to_case(Case::Snake),
+            camel: name.to_case(Case::Camel),
+            pascal: name.to_case(Case::Pascal),
+            kebab: name.to_case(Case::Kebab),
+            title: name.to_case(Case::Title),
+        }
+    }
+}
+
+/// The project templates are rendered for, as `project`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectContext {
+    #[serde(flatten)]
+    pub name: Names,
+    pub package: String,
+    pub flavors: Vec<FlavorContext>,
+    pub features: Vec<String>,
+}
+
+impl ProjectContext {
+    pub fn new(name: &str, package: &str, flavors: &[FlavorConfig], features: &[String]) -> Self {
+        let name = Names::new(name);
+        Self {
+            flavors: flavors.iter().map(|flavor| FlavorContext::new(flavor, &name.title)).collect(),
+            name,
+            package: package.to_string(),
+            features: features.to_vec(),
+        }
+    }
+
+    /// Context of an existing project, from its manifest or else its pubspec name
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        if let Some(manifest) = ProjectManifest::load(project_dir)? {
+            return Ok(Self::from(&manifest));
+        }
+
+        let pubspec_name = if vfs::exists(project_dir.join("pubspec.yaml")) {
+            Pubspec::load(project_dir)?.name()
+        } else {
+            None
+        };
+
+        let name = match pubspec_name {
+            Some(name) => name,
+            None => project_dir
+                .canonicalize()
+                .unwrap_or_else(|_| project_dir.to_path_buf())
+                .file_name()
+                .and_then(|name| name.to_str())
+                .context("Failed to determine the project name")?
+                .to_string(),
+        };
+        let package = format!("com.example.{}", name.to_case(Case::Snake));
+        Ok(Self::new(&name, &package, &[], &[]))
+    }
+}
+
+impl From<&ProjectManifest> for ProjectContext {
+    fn from(manifest: &ProjectManifest) -> Self {
+        Self::new(&manifest.name, &manifest.package_name, &manifest.flavors, &manifest.features)
+    }
+}
+
+/// A flavor with its settings resolved, as `flavor` and in `project.flavors`
+#[derive(Debug, Clone, Serialize)]
+pub struct FlavorContext {
+    #[serde(flatten)]
+    pub names: Names,
+    pub name: String,
+    pub display_name: String,
+    pub base_url: String,
+    pub suffix: String,
+}
+
+impl FlavorContext {
+    pub fn new(flavor: &FlavorConfig, project_title: &str) -> Self {
+        Self {
+            names: Names::new(&flavor.name),
+            name: flavor.name.clone(),
+            display_name: flavor.display_name(project_title),
+            base_url: flavor.base_url(),
+            suffix: flavor.suffix(),
+        }
+    }
+}
+
+/// Components a feature is generated with, as `feature.components`
+#[derive(Debug, Clone, Serialize)]
+pub struct Components {
+    pub state_management: bool,
+    pub repository: bool,
+    pub models: bool,
+    pub pages: bool,
+    pub services: bool,
+    pub utils: bool,
+    pub routing: bool,
+    pub di: bool,
+}
+
+/// The feature templates are rendered for, as `feature`
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureContext {
+    #[serde(flatten)]
+    pub name: Names,
+    pub components: Components,
+}
+
+impl FeatureContext {
+    pub fn new(params: &FeatureParams) -> Self {
+        Self {
+            name: Names::new(&params.name),
+            components: Components {
+                state_management: params.has_state_management,
+                repository: params.has_repository,
+                models: params.has_models,
+                pages: params.has_pages,
+                services: params.has_services,
+                utils: params.has_utils,
+                routing: params.needs_routing,
+                di: params.needs_di,
+            },
+        }
+    }
+}
+
+/// Everything a template can use
+///
+/// Parts left as `None` are not defined at all, so a template that uses them
+/// fails to render instead of producing incomplete code.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TemplateContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<FeatureContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flavor: Option<FlavorContext>,
+}
+
+impl TemplateContext {
+    pub fn for_project(project: &ProjectContext) -> Self {
+        Self {
+            project: Some(project.clone()),
+            ..Self::default()
+        }
+    }
+
+    pub fn for_feature(project: &ProjectContext, params: &FeatureParams) -> Self {
+        Self {
+            feature: Some(FeatureContext::new(params)),
+            ..Self::for_project(project)
+        }
+    }
+
+    pub fn for_flavor(project: &ProjectContext, flavor: &FlavorContext) -> Self {
+        Self {
+            flavor: Some(flavor.clone()),
+            ..Self::for_project(project)
+        }
+    }
+}
+
+/// A Tera filter converting a string value to a case, e.g. `{{ flavor | pascal }}`
+fn case_filter(case: Case) -> impl tera::Filter {
+    move |value: &tera::Value, _: &HashMap<String, tera::Value>| match value.as_str() {
+        Some(text) => Ok(tera::Value::String(text.to_case(case))),
+        None => Err(tera::Error::msg(format!("Case filters need a string, got {}", value))),
+    }
+}
+
+/// Text inside a single-quoted Dart string literal
+pub fn dart_string(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "\\'")
+        .replace('$', "\\$")
+        .replace('\n', "\\n")
+}
+
+/// An Android string resource value, written inside a double-quoted Gradle
+/// (Groovy or Kotlin) string as `resValue` takes it
+pub fn android_string(text: &str) -> String {
+    let mut resource = String::new();
+    if text.starts_with(['@', '?']) {
+        resource.push('\\');
+    }
+    for c in text.chars() {
+        match c {
+            '\\' | '\'' | '"' => {
+                resource.push('\\');
+                resource.push(c);
+            },
+            '\n' => resource.push_str("\\n"),
+            c => resource.push(c),
+        }
+    }
+    resource.replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$")
+}
+
+/// A value in an xcconfig file, where `//` would start a comment
+pub fn xcconfig_value(text: &str) -> String {
+    let mut value = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        value.push(c);
+        if c == '/' && chars.peek() == Some(&'/') {
+            value.push_str("$()");
+        }
+    }
+    value
+}
+
+/// A Tera filter escaping a string value for one kind of file, e.g. `{{ title | dart_string }}`
+fn escape_filter(escape: fn(&str) -> String) -> impl tera::Filter {
+    move |value: &tera::Value, _: &HashMap<String, tera::Value>| match value.as_str() {
+        Some(text) => Ok(tera::Value::String(escape(text))),
+        None => Err(tera::Error::msg(format!("Escaping filters need a string, got {}", value))),
+    }
+}
+
+/// Render template source with Tera; undefined variables are errors
+pub fn render(name: &str, source: &str, context: &TemplateContext) -> Result<String> {
+    let mut tera = Tera::default();
+    tera.register_filter("snake", case_filter(Case::Snake));
+    tera.register_filter("camel", case_filter(Case::Camel));
+    tera.register_filter("pascal", case_filter(Case::Pascal));
+    tera.register_filter("kebab", case_filter(Case::Kebab));
+    tera.register_filter("dart_string", escape_filter(dart_string));
+    tera.register_filter("android_string", escape_filter(android_string));
+    tera.register_filter("xcconfig", escape_filter(xcconfig_value));
+    tera.add_raw_template(name, source)
+        .with_context(|| format!("Failed to parse template: {}", name))?;
+
+    let context = tera::Context::from_serialize(context).context("Failed to build template context")?;
+    tera.render(name, &context)
+        .with_context(|| format!("Failed to render template: {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> ProjectContext {
+        let flavors = [FlavorConfig::named("dev"), FlavorConfig::named("prod")];
+        ProjectContext::new("pet store", "com.example.pet_store", &flavors, &["main_page".to_string()])
+    }
+
+    #[test]
+    fn test_render_context() {
+        let mut params = FeatureParams::new("order_history");
+        params.has_services = false;
+        let context = TemplateContext::for_feature(&project(), &params);
+
+        let source = "{{ project.title }} {{ feature.pascal }} {{ feature.kebab }}\n\
+            {% if feature.components.services %}services{% else %}no services{% endif %}\n\
+            {% for flavor in project.flavors %}{{ flavor.pascal }};{% endfor %}";
+        let rendered = render("test", source, &context).unwrap();
+        assert_eq!(rendered, "Pet Store OrderHistory order-history\nno services\nDev;Prod;");
+    }
+
+    #[test]
+    fn test_bundled_templates_render() {
+        let project = project();
+        let feature = TemplateContext::for_feature(&project, &FeatureParams::new("order_history"));
+        for dir in ["templates/features/common", "templates/features/main_page"] {
+            for entry in walkdir::WalkDir::new(dir).into_iter().map(Result::unwrap) {
+                if entry.file_type().is_file() {
+                    let path = entry.path().strip_prefix("templates").unwrap().to_str().unwrap();
+                    let rendered = crate::utils::render_template(path, &feature).unwrap();
+                    assert!(!rendered.contains("{{"), "{}", path);
+                }
+            }
+        }
+
+        let flavors = crate::utils::render_template("common/flavors.dart", &TemplateContext::for_project(&project)).unwrap();
+        assert!(flavors.contains("  dev,\n  prod,\n}"), "{}", flavors);
+        assert!(flavors.contains("return 'Pet Store Dev';"));
+        assert!(flavors.contains("return 'https://dev-api.example.com';"));
+        assert!(flavors.contains("return '.dev';"));
+        let dev = &project.flavors[0];
+        let main = crate::utils::render_template("common/main_flavor.dart.tmpl", &TemplateContext::for_flavor(&project, dev)).unwrap();
+        assert!(main.contains("F.appFlavor = Flavor.dev;"));
+        let main = crate::utils::render_template("common/main_flavor.dart.tmpl", &TemplateContext::for_project(&project)).unwrap();
+        assert!(!main.contains("appFlavor"));
+    }
+
+    #[test]
+    fn test_display_names_are_escaped() {
+        let qa = FlavorConfig { display_name: Some("Bob's \"Shop\"".into()), ..FlavorConfig::named("qa") };
+        let project = ProjectContext::new("shop", "com.acme.shop", &[qa], &[]);
+        let context = TemplateContext::for_project(&project);
+
+        let flavors = crate::utils::render_template("common/flavors.dart", &context).unwrap();
+        assert!(flavors.contains(r#"return 'Bob\'s "Shop"';"#), "{}", flavors);
+        let gradle = crate::utils::render_template("flavors/android/product_flavors.gradle", &context).unwrap();
+        assert!(gradle.contains(r#"resValue "string", "app_name", "Bob\\'s \\\"Shop\\\"""#), "{}", gradle);
+        let kts = crate::utils::render_template("flavors/android/product_flavors.gradle.kts", &context).unwrap();
+        assert!(kts.contains(r#"value = "Bob\\'s \\\"Shop\\\"")"#), "{}", kts);
+
+        assert_eq!(dart_string(r"$price \ 'n'"), r"\$price \\ \'n\'");
+        assert_eq!(android_string("@home $1"), r"\\@home \$1");
+        assert_eq!(xcconfig_value("Shop // QA///"), "Shop /$()/ QA/$()/$()/");
+    }
+
+    #[test]
+    fn test_overrides_fall_back_per_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(dir.join("features/common")).unwrap();
+        fs::write(dir.join("features/common/router.dart.tmpl"), "// custom router").unwrap();
+        fs::write(dir.join("features/common/extra.dart.tmpl"), "// extra").unwrap();
+        let dirs = vec![dir.clone()];
+
+        let router = find_in(&dirs, "features/common/router.dart.tmpl").unwrap().unwrap();
+        assert_eq!(router, b"// custom router");
+        let di = find_in(&dirs, "features/common/di.dart.tmpl").unwrap().unwrap();
+        assert!(String::from_utf8(di).unwrap().contains("init{{ feature.pascal }}Di"));
+        assert!(find_in(&dirs, "features/common/missing.dart.tmpl").unwrap().is_none());
+
+        let files = files_in(&dirs, "features/common").unwrap();
+        assert_eq!(files["router.dart.tmpl"], b"// custom router");
+        assert!(files.contains_key("extra.dart.tmpl"));
+        assert!(files.contains_key("ui/pages/feature_page.dart.tmpl"));
+
+        fs::write(dir.join("versions.yaml"), "dependencies:\n  dio: ^5.0.0\n").unwrap();
+        let layers = layers_in(&dirs, "versions.yaml").unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1], "dependencies:\n  dio: ^5.0.0\n");
+    }
+
+    #[test]
+    fn test_undefined_variables_fail() {
+        let context = TemplateContext::for_project(&project());
+        assert!(render("test", "{{ project.snake }}", &context).is_ok());
+        assert!(render("test", "{{ project.nmae }}", &context).is_err());
+        assert!(render("test", "class {{ feature.pascal }} {}", &context).is_err());
+        assert!(render("test", "{{ FEATURE_NAME_PASCAL }}", &TemplateContext::default()).is_err());
+    }
+}
@@ -1,147 +1,98 @@
-use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use anyhow::{Context, Result};
-use fs_extra::dir::{self, CopyOptions};
+
+use crate::regions;
+use crate::templates::{self, TemplateContext};
+use crate::vfs;
+
+/// Write a file generated from the spec, keeping the keep regions of the existing file
+///
+/// The file gets a checksum of its generated parts, so a later regeneration can
+/// refuse to overwrite hand edits made outside of keep regions unless `force` is set.
+pub fn write_generated_file(path: &Path, content: &str, force: bool) -> Result<()> {
+    let existing = if vfs::exists(path) {
+        Some(vfs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?)
+    } else {
+        None
+    };
+
+    let content = regions::merge_generated(existing.as_deref(), content, force)
+        .with_context(|| format!("Refusing to overwrite {}", path.display()))?;
+
+    if let Some(parent) = path.parent() {
+        vfs::create_dir_all(parent)?;
+    }
+    vfs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Remove a generated file that is no longer needed, unless it was edited by hand and not `force`d
+pub fn remove_generated_file(path: &Path, force: bool) -> Result<()> {
+    let content = vfs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !force && !regions::is_unedited(&content) {
+        return Err(anyhow::anyhow!(
+            "Refusing to remove {}: it was edited by hand; use --force to remove it",
+            path.display()
+        ));
+    }
+
+    vfs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+}
 
 /// Copies a template directory to destination
 pub fn copy_template_dir(template_subpath: &str, destination: &Path) -> Result<()> {
-    let template_path = std::env::current_exe()?
-        .parent()
-        .context("Failed to get executable directory")?
-        .join("templates")
-        .join(template_subpath);
-        
-    // For development, check local project paths
-    let paths_to_check = vec![
-        // Path when run from project root
-        PathBuf::from("templates").join(template_subpath),
-        // Path when run from inside project directory
-        PathBuf::from("flutter_lazy/templates").join(template_subpath),
-        // Current directory path
-        PathBuf::from("./templates").join(template_subpath),
-        // Path relative to workspace root
-        PathBuf::from("../templates").join(template_subpath),
-    ];
-    
-    // Try to find the template path
-    let mut source_path = template_path.clone();
-    let mut found = template_path.exists();
-    
-    if !found {
-        for path in &paths_to_check {
-            if path.exists() {
-                source_path = path.clone();
-                found = true;
-                break;
-            }
-        }
-    }
-    
-    if !found {
-        eprintln!("Searched in:");
-        eprintln!("  - {:?}", template_path);
-        for path in paths_to_check {
-            eprintln!("  - {:?}", path);
-        }
+    let files = templates::files(template_subpath)?;
+    if files.is_empty() {
         return Err(anyhow::anyhow!("Template directory not found: {}", template_subpath));
     }
     
-    // Create destination if it doesn't exist
-    std::fs::create_dir_all(destination)?;
-    
-    let mut copy_options = CopyOptions::new();
-    copy_options.overwrite = true;
-    
-    // Copy directory contents
-    if source_path.exists() && source_path.is_dir() {
-        dir::copy(source_path, destination, &copy_options)?;
+    // Copy the directory into the destination, overwriting existing files
+    let dir_name = Path::new(template_subpath).file_name().unwrap_or_default();
+    let target_root = destination.join(dir_name);
+    for (relative, content) in files {
+        let target = target_root.join(relative);
+        if let Some(parent) = target.parent() {
+            vfs::create_dir_all(parent)?;
+        }
+        vfs::write(&target, content)
+            .with_context(|| format!("Failed to create destination file: {:?}", target))?;
     }
     
     Ok(())
 }
 
-/// Copies a template file and replaces placeholders
-pub fn copy_template_file(template_path: &str, dest_path: &Path, replacements: &[(&str, &str)]) -> Result<()> {
-    // Determine the template path
-    let mut template_content = String::new();
-    
-    let exe_template_path = std::env::current_exe()?
-        .parent()
-        .context("Failed to get executable directory")?
-        .join("templates")
-        .join(template_path);
-        
-    // For development, check multiple local project paths
-    let paths_to_check = vec![
-        // Path when run from project root
-        PathBuf::from("templates").join(template_path),
-        // Path when run from inside project directory
-        PathBuf::from("flutter_lazy/templates").join(template_path),
-        // Current directory path
-        PathBuf::from("./templates").join(template_path),
-        // Path relative to workspace root
-        PathBuf::from("../templates").join(template_path),
-    ];
-    
-    // Try to find the template path
-    let mut template_file = None;
-    
-    // First check the executable path
-    if exe_template_path.exists() {
-        template_file = match File::open(&exe_template_path) {
-            Ok(file) => Some(file),
-            Err(_) => None,
-        };
-    }
-    
-    // If not found, check all local paths
-    if template_file.is_none() {
-        for path in &paths_to_check {
-            if path.exists() {
-                match File::open(path) {
-                    Ok(file) => {
-                        template_file = Some(file);
-                        break;
-                    },
-                    Err(_) => continue,
-                }
-            }
-        }
-    }
-    
-    // Return error if no template found
-    let mut template_file = match template_file {
-        Some(file) => file,
-        None => {
-            eprintln!("Searched in:");
-            eprintln!("  - {:?}", exe_template_path);
-            for path in paths_to_check {
-                eprintln!("  - {:?}", path);
-            }
-            return Err(anyhow::anyhow!("Template file not found: {}", template_path));
-        }
-    };
-    
-    template_file.read_to_string(&mut template_content)?;
-    
-    // Replace placeholders
-    let mut final_content = template_content;
-    for (placeholder, value) in replacements {
-        let placeholder_tag = format!("{{{{ {} }}}}", placeholder);
-        final_content = final_content.replace(&placeholder_tag, value);
-    }
+/// Renders a template file to a destination, for project scaffolding that is not regenerated
+pub fn copy_template_file(template_path: &str, dest_path: &Path, context: &TemplateContext) -> Result<()> {
+    let content = render_template(template_path, context)?;
     
     // Create parent directories if they don't exist
     if let Some(parent) = dest_path.parent() {
-        fs::create_dir_all(parent)?;
+        vfs::create_dir_all(parent)?;
     }
     
     // Write the content to the destination file
-    let mut dest_file = File::create(dest_path)
-        .with_context(|| format!("Failed to create destination file: {:?}", dest_path))?;
-        
-    dest_file.write_all(final_content.as_bytes())?;
-    
-    Ok(())
+    vfs::write(dest_path, content)
+        .with_context(|| format!("Failed to create destination file: {:?}", dest_path))
+}
+
+/// Renders a template file to a generated file, like `write_generated_file`
+pub fn generate_template_file(template_path: &str, dest_path: &Path, context: &TemplateContext, force: bool) -> Result<()> {
+    let content = render_template(template_path, context)?;
+    write_generated_file(dest_path, &content, force)
+}
+
+/// Copies a template file as is, for assets that are not rendered
+pub fn copy_template_asset(template_path: &str, dest_path: &Path) -> Result<()> {
+    let content = templates::find(template_path)?
+        .with_context(|| format!("Template file not found: {}", template_path))?;
+    if let Some(parent) = dest_path.parent() {
+        vfs::create_dir_all(parent)?;
+    }
+    vfs::write(dest_path, content)
+        .with_context(|| format!("Failed to create destination file: {:?}", dest_path))
+}
+
+/// Reads a template file and renders it with Tera
+pub fn render_template(template_path: &str, context: &TemplateContext) -> Result<String> {
+    let template_content = templates::read(template_path)?;
+    templates::render(template_path, &template_content, context)
 }
@@ -2,9 +2,11 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
-use serde_yaml;
 use console::style;
 
+use crate::config::FlavorConfig;
+use crate::templates;
+
 // Structure to represent validation rules for project structure
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ValidationRules {
@@ -20,6 +22,7 @@ pub struct FileContentCheck {
 }
 
 pub struct ValidationResult {
+    #[allow(dead_code)]
     pub project_path: PathBuf,
     pub passed: bool,
     pub missing_directories: Vec<PathBuf>,
@@ -79,6 +82,7 @@ impl ValidationSystem {
     }
 
     /// Load validation rules from a file
+    #[allow(dead_code)]
     pub fn from_file(file_path: &Path) -> Result<Self> {
         let content = fs::read_to_string(file_path)
             .context(format!("Failed to read validation rules file: {}", file_path.display()))?;
@@ -92,14 +96,9 @@ impl ValidationSystem {
     /// Create default validation rules for new project creation
     pub fn default_new_project_rules() -> ValidationRules {
         // Try to load from template file first
-        let template_path = Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("templates/validation/project_validation.yml");
-            
-        if template_path.exists() {
-            if let Ok(content) = fs::read_to_string(&template_path) {
-                if let Ok(rules) = serde_yaml::from_str::<ValidationRules>(&content) {
-                    return rules;
-                }
+        if let Ok(content) = templates::read("validation/project_validation.yml") {
+            if let Ok(rules) = serde_yaml::from_str::<ValidationRules>(&content) {
+                return rules;
             }
         }
         
@@ -147,17 +146,12 @@ impl ValidationSystem {
     /// Create default validation rules for feature creation
     pub fn default_feature_rules(feature_name: &str) -> ValidationRules {
         // Try to load from template file first
-        let template_path = Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("templates/validation/feature_validation.yml");
+        if let Ok(content) = templates::read("validation/feature_validation.yml") {
+            // Replace {feature_name} placeholder with actual feature name
+            let content = content.replace("{feature_name}", feature_name);
             
-        if template_path.exists() {
-            if let Ok(content) = fs::read_to_string(&template_path) {
-                // Replace {feature_name} placeholder with actual feature name
-                let content = content.replace("{feature_name}", feature_name);
-                
-                if let Ok(rules) = serde_yaml::from_str::<ValidationRules>(&content) {
-                    return rules;
-                }
+            if let Ok(rules) = serde_yaml::from_str::<ValidationRules>(&content) {
+                return rules;
             }
         }
         
@@ -227,6 +221,7 @@ impl ValidationSystem {
     }
 
     /// Validate a specific feature structure
+    #[allow(dead_code)]
     pub fn validate_feature(&self, project_path: &Path, _feature_name: &str) -> ValidationResult {
         self.validate_project(project_path)
     }
@@ -240,6 +235,7 @@ impl ValidationSystem {
 }
 
 // Helper function to create custom validation rules
+#[allow(dead_code)]
 pub fn create_custom_validation(
     required_dirs: Vec<String>,
     required_files: Vec<String>,
@@ -247,12 +243,13 @@ pub fn create_custom_validation(
 ) -> ValidationRules {
     ValidationRules {
         required_directories: required_dirs,
-        required_files: required_files,
+        required_files,
         file_content_checks: content_checks,
     }
 }
 
 // Function to save validation rules to a file for future reference
+#[allow(dead_code)]
 pub fn save_validation_rules(rules: &ValidationRules, file_path: &Path) -> Result<()> {
     let yaml = serde_yaml::to_string(rules)
         .context("Failed to serialize validation rules to YAML")?;
@@ -266,17 +263,12 @@ pub fn save_validation_rules(rules: &ValidationRules, file_path: &Path) -> Resul
 // Custom validation for API feature generation
 pub fn create_api_feature_validation(feature_name: &str) -> ValidationRules {
     // Try to load from template file first
-    let template_path = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .join("templates/validation/api_feature_validation.yml");
+    if let Ok(content) = templates::read("validation/api_feature_validation.yml") {
+        // Replace {feature_name} placeholder with actual feature name
+        let content = content.replace("{feature_name}", feature_name);
         
-    if template_path.exists() {
-        if let Ok(content) = fs::read_to_string(&template_path) {
-            // Replace {feature_name} placeholder with actual feature name
-            let content = content.replace("{feature_name}", feature_name);
-            
-            if let Ok(rules) = serde_yaml::from_str::<ValidationRules>(&content) {
-                return rules;
-            }
+        if let Ok(rules) = serde_yaml::from_str::<ValidationRules>(&content) {
+            return rules;
         }
     }
     
@@ -297,10 +289,37 @@ pub fn create_api_feature_validation(feature_name: &str) -> ValidationRules {
     }
 }
 
+/// Project rules extended with checks for the recorded flavors and features
+pub fn create_manifest_validation(flavors: &[FlavorConfig], features: &[String]) -> ValidationRules {
+    let mut rules = ValidationSystem::default_new_project_rules();
+    
+    // Every flavor gets its own entry point
+    rules.required_files.extend(flavors.iter().map(|flavor| format!("lib/main_{}.dart", flavor.name)));
+    
+    // Add feature-specific rules
+    for feature in features {
+        rules.required_directories.push(format!("lib/features/{}", feature));
+        match feature.as_str() {
+            "auth" => {
+                rules.required_files.push("lib/features/auth/presentation/pages/login_page.dart".to_string());
+            },
+            "notifications" => {
+                rules.required_files.push("lib/features/notifications/domain/repositories/notification_repository.dart".to_string());
+            },
+            "main_page" => {
+                rules.required_files.push("lib/features/main_page/presentation/pages/main_page.dart".to_string());
+            },
+            _ => {}
+        }
+    }
+    
+    rules
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    
     use std::path::PathBuf;
     
     #[test]
@@ -0,0 +1,372 @@
+// vfs.rs
+// Filesystem writes staged in memory, then written together or previewed by a dry run
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use console::style;
+use similar::TextDiff;
+
+/// Writes staged by a transaction or planned by a dry run
+#[derive(Debug, Default)]
+struct Plan {
+    /// Content of every written file, or `None` once it is removed
+    files: BTreeMap<PathBuf, Option<Vec<u8>>>,
+    /// Directories that would be created
+    dirs: BTreeSet<PathBuf>,
+    /// Whether the plan is only printed, never written
+    dry_run: bool,
+}
+
+impl Plan {
+    fn exists(&self, path: &Path) -> bool {
+        match self.files.get(path) {
+            Some(content) => content.is_some(),
+            None => self.dirs.contains(path) || path.exists(),
+        }
+    }
+}
+
+thread_local! {
+    /// The transaction or dry run in progress, if any; generation runs on one thread
+    static PLAN: RefCell<Option<Plan>> = const { RefCell::new(None) };
+}
+
+/// Stage every following write in memory instead of touching disk
+fn start(dry_run: bool) {
+    PLAN.with(|plan| *plan.borrow_mut() = Some(Plan { dry_run, ..Plan::default() }));
+}
+
+/// Drop the staged writes, returning them
+fn take() -> Option<Plan> {
+    PLAN.with(|plan| plan.borrow_mut().take())
+}
+
+/// Whether writes are only being planned
+pub fn is_dry_run() -> bool {
+    PLAN.with(|plan| plan.borrow().as_ref().is_some_and(|plan| plan.dry_run))
+}
+
+/// Run a generation step, writing its files only if all of it succeeds
+///
+/// Nothing is written when the step fails, and if writing the files fails
+/// partway, files already written are removed or restored.
+pub fn transaction<T>(generate: impl FnOnce() -> Result<T>) -> Result<T> {
+    start(false);
+    let result = generate();
+    let plan = take().unwrap_or_default();
+
+    let value = result.context("Generation failed, so no files were changed")?;
+    let mut journal = Journal::default();
+    if let Err(err) = journal.apply(&plan) {
+        journal.undo();
+        return Err(err.context("Failed to write the generated files; changes were rolled back"));
+    }
+    Ok(value)
+}
+
+/// Run a generation step without writing anything, then print what it would change
+pub fn dry_run(root: &Path, generate: impl FnOnce() -> Result<()>) -> Result<()> {
+    start(true);
+    let result = generate();
+    let plan = take().unwrap_or_default();
+
+    result?;
+    print_plan(root, &plan);
+    Ok(())
+}
+
+/// What committing a plan has done so far, to undo it if a write fails
+#[derive(Debug, Default)]
+struct Journal {
+    created_dirs: Vec<PathBuf>,
+    /// Written or removed files with their previous content
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Journal {
+    fn apply(&mut self, plan: &Plan) -> Result<()> {
+        // Parents sort before their children
+        for dir in &plan.dirs {
+            if !dir.exists() {
+                fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+                self.created_dirs.push(dir.clone());
+            }
+        }
+
+        for (path, content) in &plan.files {
+            self.files.push((path.clone(), fs::read(path).ok()));
+            match content {
+                Some(content) => fs::write(path, content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                None if path.exists() => fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?,
+                None => {},
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Best effort: restore every file touched, then remove the created directories
+    fn undo(&self) {
+        for (path, previous) in self.files.iter().rev() {
+            let _ = match previous {
+                Some(content) => fs::write(path, content),
+                None => fs::remove_file(path),
+            };
+        }
+        for dir in self.created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Write a file, like `fs::write`
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref();
+    PLAN.with(|plan| match plan.borrow_mut().as_mut() {
+        Some(plan) => {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                if !plan.exists(parent) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("Directory {} does not exist", parent.display()),
+                    ));
+                }
+            }
+            plan.files.insert(path.to_path_buf(), Some(contents.as_ref().to_vec()));
+            Ok(())
+        },
+        None => fs::write(path, contents),
+    })
+}
+
+/// Create a directory and its missing parents, like `fs::create_dir_all`
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    PLAN.with(|plan| match plan.borrow_mut().as_mut() {
+        Some(plan) => {
+            for dir in path.ancestors().filter(|dir| !dir.as_os_str().is_empty()) {
+                if plan.exists(dir) {
+                    break;
+                }
+                plan.dirs.insert(dir.to_path_buf());
+            }
+            Ok(())
+        },
+        None => fs::create_dir_all(path),
+    })
+}
+
+/// Remove a file, like `fs::remove_file`
+pub fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    PLAN.with(|plan| match plan.borrow_mut().as_mut() {
+        Some(plan) => {
+            if !plan.exists(path) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("File {} does not exist", path.display()),
+                ));
+            }
+            plan.files.insert(path.to_path_buf(), None);
+            Ok(())
+        },
+        None => fs::remove_file(path),
+    })
+}
+
+/// Read a file as it would be after the planned writes, like `fs::read_to_string`
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let path = path.as_ref();
+    let planned = PLAN.with(|plan| plan.borrow().as_ref().and_then(|plan| plan.files.get(path).cloned()));
+    match planned {
+        Some(Some(content)) => String::from_utf8(content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        Some(None) => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("File {} was removed", path.display()),
+        )),
+        None => fs::read_to_string(path),
+    }
+}
+
+/// Whether a path exists after the planned writes, like `Path::exists`
+pub fn exists<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    PLAN.with(|plan| match plan.borrow().as_ref() {
+        Some(plan) => plan.exists(path),
+        None => path.exists(),
+    })
+}
+
+/// What a planned file does to the file on disk
+#[derive(Debug, PartialEq)]
+enum Change {
+    Created,
+    Modified { old: String, new: String },
+    Unchanged,
+    Removed,
+}
+
+fn change(path: &Path, content: Option<&[u8]>) -> Change {
+    let existing = fs::read(path).ok();
+    match (existing, content) {
+        (None, _) => Change::Created,
+        (Some(_), None) => Change::Removed,
+        (Some(old), Some(new)) if old == new => Change::Unchanged,
+        (Some(old), Some(new)) => Change::Modified {
+            old: String::from_utf8_lossy(&old).into_owned(),
+            new: String::from_utf8_lossy(new).into_owned(),
+        },
+    }
+}
+
+/// Print the planned file tree and a diff of every modified file
+fn print_plan(root: &Path, plan: &Plan) {
+    let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+
+    let changes: BTreeMap<PathBuf, Change> = plan.files
+        .iter()
+        .map(|(path, content)| (relative(path), change(path, content.as_deref())))
+        .filter(|(_, change)| *change != Change::Unchanged)
+        .collect();
+
+    // Every planned entry below the root, with the directories leading to it
+    let new_dirs: BTreeSet<PathBuf> = plan.dirs.iter().map(|dir| relative(dir)).collect();
+    let mut tree = BTreeSet::new();
+    for path in changes.keys().chain(&new_dirs) {
+        for ancestor in path.ancestors().filter(|ancestor| !ancestor.as_os_str().is_empty()) {
+            tree.insert(ancestor.to_path_buf());
+        }
+    }
+
+    println!("\n{}", style("Dry run: no files were written").bold().cyan());
+    if tree.is_empty() {
+        println!("\n✅ Nothing would change");
+        return;
+    }
+
+    println!("\nPlanned changes in {}:", style(root.display()).bold());
+    for path in &tree {
+        let depth = path.components().count();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let label = match changes.get(path) {
+            Some(Change::Created) => style("(new)").green().to_string(),
+            Some(Change::Modified { .. }) => style("(modified)").yellow().to_string(),
+            Some(Change::Removed) => style("(removed)").red().to_string(),
+            _ if new_dirs.contains(path) => style("(new)").green().to_string(),
+            _ => String::new(),
+        };
+        let slash = if changes.contains_key(path) { "" } else { "/" };
+        let line = format!("{}{}{} {}", "  ".repeat(depth), name, slash, label);
+        println!("{}", line.trim_end());
+    }
+
+    for (path, change) in &changes {
+        let Change::Modified { old, new } = change else {
+            continue;
+        };
+        let name = path.display().to_string();
+        let diff = TextDiff::from_lines(old, new);
+        println!();
+        for line in diff.unified_diff().context_radius(3).header(&name, &name).to_string().lines() {
+            if line.starts_with("+++") || line.starts_with("---") {
+                println!("{}", style(line).bold());
+            } else if line.starts_with('+') {
+                println!("{}", style(line).green());
+            } else if line.starts_with('-') {
+                println!("{}", style(line).red());
+            } else if line.starts_with("@@") {
+                println!("{}", style(line).cyan());
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+
+    let count = |wanted: fn(&Change) -> bool| changes.values().filter(|change| wanted(change)).count();
+    println!(
+        "\n{} created, {} modified, {} removed",
+        count(|change| matches!(change, Change::Created)),
+        count(|change| matches!(change, Change::Modified { .. })),
+        count(|change| matches!(change, Change::Removed)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dry_run_touches_nothing() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("router.dart"), "routes: [\n]\n").unwrap();
+
+        start(true);
+        create_dir_all(dir.join("lib/features/pets")).unwrap();
+        write(dir.join("lib/features/pets/router.dart"), "// pets").unwrap();
+        write(dir.join("router.dart"), "routes: [\n  pets,\n]\n").unwrap();
+        assert!(write(dir.join("missing/file.dart"), "").is_err());
+
+        // Reads see the planned writes
+        assert!(exists(dir.join("lib/features/pets")));
+        assert_eq!(read_to_string(dir.join("lib/features/pets/router.dart")).unwrap(), "// pets");
+        assert_eq!(
+            change(&dir.join("router.dart"), Some(read_to_string(dir.join("router.dart")).unwrap().as_bytes())),
+            Change::Modified { old: "routes: [\n]\n".into(), new: "routes: [\n  pets,\n]\n".into() }
+        );
+        print_plan(&dir, &take().unwrap());
+
+        let router = fs::read_to_string(dir.join("router.dart")).unwrap();
+        let created = dir.join("lib").exists();
+
+        assert!(!is_dry_run());
+        assert_eq!(router, "routes: [\n]\n");
+        assert!(!created);
+    }
+
+    #[test]
+    fn test_failed_generation_writes_nothing() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b_existing.txt"), "before").unwrap();
+        fs::write(dir.join("c_blocker"), "a file, not a directory").unwrap();
+
+        // A failing step leaves the disk alone
+        let failed = transaction(|| -> Result<()> {
+            create_dir_all(dir.join("lib/features/auth"))?;
+            write(dir.join("lib/features/auth/router.dart"), "// auth")?;
+            Err(anyhow::anyhow!("Template file not found"))
+        });
+        assert!(failed.is_err());
+        assert!(!dir.join("lib").exists());
+
+        // A write failing while committing undoes the writes before it
+        let failed = transaction(|| -> Result<()> {
+            create_dir_all(dir.join("a_new"))?;
+            write(dir.join("a_new/file.txt"), "new")?;
+            write(dir.join("b_existing.txt"), "after")?;
+            write(dir.join("c_blocker/file.txt"), "fails")?;
+            Ok(())
+        });
+        let existing = fs::read_to_string(dir.join("b_existing.txt")).unwrap();
+        let created = dir.join("a_new").exists();
+
+        let succeeded = transaction(|| write(dir.join("b_existing.txt"), "after").map_err(Into::into));
+        let committed = fs::read_to_string(dir.join("b_existing.txt")).unwrap();
+
+        assert!(failed.is_err());
+        assert_eq!(existing, "before");
+        assert!(!created);
+        assert!(succeeded.is_ok());
+        assert_eq!(committed, "after");
+    }
+}
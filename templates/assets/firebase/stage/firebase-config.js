@@ -1,15 +0,0 @@
-// Placeholder Firebase configuration for staging environment
-// You should replace this with your actual Firebase configuration
-
-const firebaseConfig = {
-  apiKey: "your-api-key-here",
-  authDomain: "stage-your-app.firebaseapp.com",
-  projectId: "stage-your-app",
-  storageBucket: "stage-your-app.appspot.com",
-  messagingSenderId: "000000000000",
-  appId: "1:000000000000:web:0000000000000000000000",
-  measurementId: "G-XXXXXXXXXXXX"
-};
-
-// Initialize Firebase
-firebase.initializeApp(firebaseConfig);
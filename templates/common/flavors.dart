@@ -1,7 +1,7 @@
 enum Flavor {
-  dev,
-  stage,
-  prod,
+{%- for flavor in project.flavors %}
+  {{ flavor.camel }},
+{%- endfor %}
 }
 
 class F {
@@ -9,31 +9,38 @@ class F {
 
   static String get title {
     switch (appFlavor) {
-      case Flavor.dev:
-        return 'Dev App';
-      case Flavor.stage:
-        return 'Stage App';
-      case Flavor.prod:
-        return 'App';
+{%- for flavor in project.flavors %}
+      case Flavor.{{ flavor.camel }}:
+        return '{{ flavor.display_name | dart_string }}';
+{%- endfor %}
       default:
-        return 'App';
+        return '{{ project.title | dart_string }}';
     }
   }
 
   static String get baseUrl {
     switch (appFlavor) {
-      case Flavor.dev:
-        return 'https://dev-api.example.com';
-      case Flavor.stage:
-        return 'https://stage-api.example.com';
-      case Flavor.prod:
-        return 'https://api.example.com';
+{%- for flavor in project.flavors %}
+      case Flavor.{{ flavor.camel }}:
+        return '{{ flavor.base_url | dart_string }}';
+{%- endfor %}
       default:
         return 'https://api.example.com';
     }
   }
-  
-  static bool get isDev => appFlavor == Flavor.dev;
-  static bool get isStage => appFlavor == Flavor.stage;
-  static bool get isProd => appFlavor == Flavor.prod;
+
+  /// Suffix of the application/bundle id, matching the native flavor setup
+  static String get bundleIdSuffix {
+    switch (appFlavor) {
+{%- for flavor in project.flavors %}
+      case Flavor.{{ flavor.camel }}:
+        return '{{ flavor.suffix }}';
+{%- endfor %}
+      default:
+        return '';
+    }
+  }
+{% for flavor in project.flavors %}
+  static bool get is{{ flavor.pascal }} => appFlavor == Flavor.{{ flavor.camel }};
+{%- endfor %}
 }
@@ -0,0 +1,15 @@
+    // flutter_lazy:flavors-start
+    flavorDimensions += "flavor"
+
+    productFlavors {
+{%- for flavor in project.flavors %}
+        create("{{ flavor.name }}") {
+            dimension = "flavor"
+{%- if flavor.suffix %}
+            applicationIdSuffix = "{{ flavor.suffix }}"
+{%- endif %}
+            resValue(type = "string", name = "app_name", value = "{{ flavor.display_name | android_string }}")
+        }
+{%- endfor %}
+    }
+    // flutter_lazy:flavors-end